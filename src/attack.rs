@@ -1,30 +1,63 @@
 use bevy::prelude::*;
 
-use crate::{health::Health, movement::Velocity, schedule::InGameSet};
+use crate::{
+    collisions::Collider,
+    damage::{DamageEvent, DamageKind, EntityDied},
+    detection::DetectionGroups,
+    movement::Velocity,
+    projectile::{spawn_projectile, ProjectileAttack},
+    schedule::InGameSet,
+    status::StatusEffects,
+};
 
 pub struct AttackPlugin;
 
 impl Plugin for AttackPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            (attack_occurance, remove_attack_occurances)
+            FixedUpdate,
+            (
+                stop_out_of_range_attacks,
+                attack_occurance,
+                remove_attack_occurances,
+            )
                 .chain()
                 .in_set(InGameSet::EntityUpdates),
-        )
-        .add_event::<AttackOccuranceDeathEvent>();
+        );
     }
 }
 
 #[derive(Component, Debug)]
 pub struct Attack {
     pub amount: f32,
+    pub kind: DamageKind,
+    /// Distance to the closest point of the target from which the attack can be made.
+    pub range: f32,
     pub rate: Timer,
 }
 
 impl Attack {
-    pub fn new(amount: f32, rate: Timer) -> Self {
-        Self { amount, rate }
+    pub fn new(amount: f32, kind: DamageKind, range: f32, rate: Timer) -> Self {
+        Self {
+            amount,
+            kind,
+            range,
+            rate,
+        }
+    }
+
+    pub fn reaches(
+        &self,
+        position: Vec2,
+        target_transform: &GlobalTransform,
+        target_collider: Option<&Collider>,
+    ) -> bool {
+        let distance = match target_collider {
+            Some(collider) => collider.shape.distance_to_point(target_transform, position),
+            None => position.distance(target_transform.translation().truncate()),
+        };
+
+        distance <= self.range
     }
 }
 
@@ -40,57 +73,104 @@ impl AttackOccurance {
     }
 }
 
-#[derive(Event, Debug)]
-pub struct AttackOccuranceDeathEvent {
-    pub target_entity: Entity,
-}
+//  targets get knocked back or walk off mid-attack
+fn stop_out_of_range_attacks(
+    mut commands: Commands,
+    attacker_query: Query<(Entity, &GlobalTransform, &Attack, &AttackOccurance)>,
+    target_query: Query<(&GlobalTransform, Option<&Collider>)>,
+) {
+    for (entity, transform, attack, occurance) in attacker_query.iter() {
+        let Ok((target_transform, target_collider)) = target_query.get(occurance.target) else {
+            continue;
+        };
 
-impl AttackOccuranceDeathEvent {
-    pub fn new(target_entity: Entity) -> Self {
-        Self { target_entity }
+        if !attack.reaches(
+            transform.translation().truncate(),
+            target_transform,
+            target_collider,
+        ) {
+            commands.entity(entity).remove::<AttackOccurance>();
+        }
     }
 }
 
-fn attack_occurance(
+type Attacker = (
+    &'static mut Attack,
+    Option<&'static mut Velocity>,
+    Option<&'static StatusEffects>,
+);
+
+pub fn attack_occurance(
+    mut commands: Commands,
     occurances: Query<&AttackOccurance>,
-    mut attacker_query: Query<(&mut Attack, &mut Velocity), With<AttackOccurance>>,
-    mut target_query: Query<&mut Health>,
+    mut attacker_query: Query<Attacker, With<AttackOccurance>>,
+    ranged_attacker_query: Query<(&ProjectileAttack, &DetectionGroups)>,
+    transform_query: Query<&GlobalTransform>,
     time: Res<Time>,
-    mut target_death_event_writer: EventWriter<AttackOccuranceDeathEvent>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
 ) {
     for occurance in occurances.iter() {
-        let Ok((mut attack, mut velocity)) = attacker_query.get_mut(occurance.attacker) else {
+        let Ok((mut attack, velocity, status_effects)) = attacker_query.get_mut(occurance.attacker)
+        else {
             continue;
         };
 
-        //  first: stop movement
-        velocity.value = Vec3::ZERO;
+        //  first: stop movement, if any
+        if let Some(mut velocity) = velocity {
+            velocity.value = Vec3::ZERO;
+        }
 
-        //  second: attack at a consistant rate
-        attack.rate.tick(time.delta());
+        //  second: attack at a consistant rate, unless slowed or stunned
+        let speed_scale = status_effects.map_or(1.0, StatusEffects::speed_scale);
+        attack.rate.tick(time.delta().mul_f32(speed_scale));
 
-        if attack.rate.just_finished() {
-            let Ok(mut health) = target_query.get_mut(occurance.target) else {
+        if !attack.rate.just_finished() {
+            continue;
+        }
+
+        //  ranged attackers leave the damage to their projectile
+        if let Ok((projectile_attack, detection_groups)) =
+            ranged_attacker_query.get(occurance.attacker)
+        {
+            let Ok([attacker_transform, target_transform]) =
+                transform_query.get_many([occurance.attacker, occurance.target])
+            else {
+                //  the target was already destroyed by something else
+                commands
+                    .entity(occurance.attacker)
+                    .remove::<AttackOccurance>();
                 continue;
             };
 
-            health.value -= attack.amount;
-
-            if health.value <= 0.0 {
-                target_death_event_writer.send(AttackOccuranceDeathEvent::new(occurance.target));
-            }
+            spawn_projectile(
+                &mut commands,
+                occurance.attacker,
+                projectile_attack,
+                &attack,
+                attacker_transform.translation(),
+                target_transform.translation(),
+                detection_groups.filters,
+            );
+            continue;
         }
+
+        damage_event_writer.send(DamageEvent::new(
+            occurance.attacker,
+            occurance.target,
+            attack.amount,
+            attack.kind,
+        ));
     }
 }
 
 fn remove_attack_occurances(
     mut commands: Commands,
-    mut target_death_event_reader: EventReader<AttackOccuranceDeathEvent>,
+    mut entity_died_reader: EventReader<EntityDied>,
     mut attackers: Query<&mut AttackOccurance>,
 ) {
-    for &AttackOccuranceDeathEvent { target_entity } in target_death_event_reader.read() {
+    for event in entity_died_reader.read() {
         for occurance in attackers.iter_mut() {
-            if occurance.target == target_entity {
+            if occurance.target == event.entity {
                 commands
                     .entity(occurance.attacker)
                     .remove::<AttackOccurance>();
@@ -98,3 +178,71 @@ fn remove_attack_occurances(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collisions::{CollisionGroups, CollisionsPlugin},
+        detection::{DetectionGroups, DetectionPlugin, Target, Tracker},
+        group::Group,
+        health::Health,
+        schedule::test_app,
+        shape::ColliderShape,
+    };
+
+    #[test]
+    fn range_is_measured_to_the_closest_point_every_tick() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DetectionPlugin, AttackPlugin));
+
+        let attacker = app
+            .world
+            .spawn((
+                Tracker::new(200.0),
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+                Attack::new(
+                    20.0,
+                    DamageKind::Pierce,
+                    20.0,
+                    Timer::from_seconds(1.0, TimerMode::Repeating),
+                ),
+                Velocity::new(Vec3::ZERO),
+                TransformBundle::default(),
+            ))
+            .id();
+        //  a tall wall, well within its bounding radius but not its width
+        let target = app
+            .world
+            .spawn((
+                Target,
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Health::new(100.0),
+                Collider::new(ColliderShape::Aabb {
+                    half_extents: Vec2::new(10.0, 50.0),
+                }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(35.0, 0.0, 0.0)),
+            ))
+            .id();
+        let move_target = |app: &mut App, x: f32| {
+            app.world
+                .get_mut::<Transform>(target)
+                .unwrap()
+                .translation
+                .x = x;
+            app.update();
+            app.update();
+        };
+
+        move_target(&mut app, 35.0);
+        assert!(app.world.get::<AttackOccurance>(attacker).is_none());
+
+        move_target(&mut app, 25.0);
+        assert!(app.world.get::<AttackOccurance>(attacker).is_some());
+
+        //  knocked out of reach mid-attack
+        move_target(&mut app, 60.0);
+        assert!(app.world.get::<AttackOccurance>(attacker).is_none());
+    }
+}
@@ -1,15 +1,22 @@
 use bevy::prelude::*;
 
 use crate::{
-    collisions::{Bounce, Collider, CollisionDamage, CollisionGroups},
-    detection::{DetectionEvent, DetectionGroups, Target, Tracker},
-    footman::Footman,
+    attack::AttackOccurance,
+    collisions::{
+        Bounce, Collider, CollisionDamage, CollisionDamageMode, CollisionGroups, FastMover, Mass,
+        Static,
+    },
+    damage::{AreaDamage, DamageKind, DeathCause, EntityDied, Falloff},
+    detection::{
+        Cloak, CurrentTarget, DetectionGroups, Target, TargetLost, TargetingPolicy, Tracker,
+    },
     group::Group,
-    health::Health,
-    movement::{Acceleration, KinematicBundle, Velocity},
+    health::{Health, HealthBar},
+    movement::{Acceleration, KinematicBundle, LinearDrag, MaxSpeed, Velocity},
     player::Player,
-    schedule::InGameSet,
-    tower::Tower,
+    schedule::{InGameSet, InputSet, TransformInterpolation},
+    shape::ColliderShape,
+    status::{OnHitEffects, StatusEffect, StatusKind},
     Mana,
 };
 
@@ -19,32 +26,40 @@ const SPAWNER_SPAWN_RATE: f32 = 2.0;
 const SPAWNER_HEALTH: f32 = 80.0;
 const SPAWNER_COLLIDER_RADIUS: f32 = 16.0;
 const SPAWNER_COST: f32 = 20.0;
+const SPAWNER_CLOAK: f32 = 0.5;
+const SPAWNER_HEALTH_BAR_WIDTH: f32 = 32.0;
+const SPAWNER_HEALTH_BAR_OFFSET: f32 = 24.0;
 
 const BUBBLE_SPAWN_OFFSET: f32 = 6.0;
 const BUBBLE_SPRITE_LAYER: f32 = 1.0;
 const BUBBLE_LIFETIME: f32 = 6.0;
 const BUBBLE_ACCELERATION_RATE: f32 = 1800.;
+const BUBBLE_MAX_SPEED: f32 = 320.0;
+const BUBBLE_LINEAR_DRAG: f32 = 0.5;
 const BUBBLE_COLLIDER_RADIUS: f32 = 8.0;
 const BUBBLE_HEALTH: f32 = 1.0;
 const BUBBLE_COLLISION_DAMAGE: f32 = 3.0;
 const BUBBLE_BOUNCINESS: f32 = 0.9;
+const BUBBLE_MASS: f32 = 1.0;
 const BUBBLE_DETECTION_RADIUS: f32 = 420.0;
+const BUBBLE_POP_DAMAGE: f32 = 2.0;
+const BUBBLE_POP_RADIUS: f32 = 32.0;
+const BUBBLE_SLOW: f32 = 0.3;
+const BUBBLE_SLOW_DURATION: f32 = 1.5;
 
 pub struct BubblePlugin;
 
 impl Plugin for BubblePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (bubble_lifetime).in_set(InGameSet::DespawnEntities))
-            .add_systems(
-                Update,
-                (
-                    spawn_bubble_spawner,
-                    spawn_bubble,
-                    tracking::<Footman>,
-                    tracking::<Tower>,
-                )
-                    .in_set(InGameSet::EntityUpdates),
-            );
+        app.add_systems(
+            FixedUpdate,
+            (bubble_lifetime).in_set(InGameSet::DespawnEntities),
+        )
+        .add_systems(Update, spawn_bubble_spawner.in_set(InputSet))
+        .add_systems(
+            FixedUpdate,
+            (spawn_bubble, tracking, stop_tracking).in_set(InGameSet::EntityUpdates),
+        );
     }
 }
 
@@ -90,11 +105,16 @@ fn spawn_bubble_spawner(
                 },
                 ..default()
             },
-            Collider::new(SPAWNER_COLLIDER_RADIUS),
+            Collider::new(ColliderShape::Circle {
+                radius: SPAWNER_COLLIDER_RADIUS,
+            }),
             CollisionGroups::new(Group::ALLY, Group::NONE),
-            DetectionGroups::new(Group::ALLY, Group::NONE),
+            Static,
+            DetectionGroups::new(Group::ALLY | Group::STRUCTURE, Group::NONE),
             Target,
+            Cloak::new(SPAWNER_CLOAK),
             Health::new(SPAWNER_HEALTH),
+            HealthBar::new(SPAWNER_HEALTH_BAR_WIDTH, SPAWNER_HEALTH_BAR_OFFSET),
             BubbleSpawner {
                 spawn_rate: Timer::from_seconds(SPAWNER_SPAWN_RATE, TimerMode::Repeating),
             },
@@ -128,17 +148,46 @@ fn spawn_bubble(
                     },
                     ..default()
                 },
-                KinematicBundle {
-                    velocity: Velocity::new(Vec3::ZERO),
-                    acceleration: Acceleration::new(Vec3::ZERO),
-                },
-                Collider::new(BUBBLE_COLLIDER_RADIUS),
+                (
+                    KinematicBundle {
+                        velocity: Velocity::new(Vec3::ZERO),
+                        acceleration: Acceleration::new(Vec3::ZERO),
+                        interpolation: TransformInterpolation::default(),
+                    },
+                    MaxSpeed::new(BUBBLE_MAX_SPEED),
+                    LinearDrag::new(BUBBLE_LINEAR_DRAG),
+                ),
+                Collider::new(ColliderShape::Circle {
+                    radius: BUBBLE_COLLIDER_RADIUS,
+                }),
                 CollisionGroups::new(Group::ALLY, Group::ENEMY),
                 Health::new(BUBBLE_HEALTH),
-                CollisionDamage::new(BUBBLE_COLLISION_DAMAGE),
+                CollisionDamage::new(
+                    BUBBLE_COLLISION_DAMAGE,
+                    DamageKind::Magic,
+                    CollisionDamageMode::PerTick,
+                ),
                 Bounce::new(BUBBLE_BOUNCINESS),
-                DetectionGroups::new(Group::ALLY, Group::ENEMY),
-                Tracker::new(BUBBLE_DETECTION_RADIUS),
+                Mass::new(BUBBLE_MASS),
+                FastMover::default(),
+                AreaDamage::new(
+                    BUBBLE_POP_DAMAGE,
+                    DamageKind::Magic,
+                    BUBBLE_POP_RADIUS,
+                    Falloff::Linear,
+                    Group::ENEMY,
+                ),
+                OnHitEffects::new(vec![StatusEffect::new(
+                    StatusKind::Slow,
+                    BUBBLE_SLOW,
+                    BUBBLE_SLOW_DURATION,
+                )]),
+                (
+                    DetectionGroups::new(Group::ALLY, Group::ENEMY),
+                    Tracker::new(BUBBLE_DETECTION_RADIUS),
+                    TargetingPolicy::Sticky,
+                    Target,
+                ),
                 Bubble {
                     lifetime: Timer::from_seconds(BUBBLE_LIFETIME, TimerMode::Once),
                 },
@@ -149,35 +198,36 @@ fn spawn_bubble(
 }
 
 fn bubble_lifetime(
-    mut commands: Commands,
     time: Res<Time>,
-    mut bubbles: Query<(Entity, &mut Bubble)>,
+    mut bubbles: Query<(Entity, &mut Bubble, &mut Health, &GlobalTransform)>,
+    mut entity_died_writer: EventWriter<EntityDied>,
 ) {
-    for (bubble_entity, mut bubble) in &mut bubbles {
+    for (bubble_entity, mut bubble, mut health, transform) in &mut bubbles {
         bubble.lifetime.tick(time.delta());
 
-        if bubble.lifetime.finished() {
-            commands.entity(bubble_entity).despawn_recursive();
+        //  bubbles that were killed already died
+        if bubble.lifetime.finished() && health.current > 0.0 {
+            //  pops and gets despawned like any other death
+            health.current = 0.0;
+            entity_died_writer.send(EntityDied::new(
+                bubble_entity,
+                None,
+                DeathCause::Expired,
+                transform.translation().truncate(),
+            ));
         }
     }
 }
 
-fn tracking<T: Component>(
-    mut detection_event_reader: EventReader<DetectionEvent>,
-    mut tracker_query: Query<(&GlobalTransform, &mut Acceleration), With<Bubble>>,
-    target_query: Query<&GlobalTransform, With<T>>,
-) {
-    for &DetectionEvent {
-        tracker_entity,
-        target_entity,
-    } in detection_event_reader.read()
-    {
-        let Ok((tracker_transform, mut acceleration)) = tracker_query.get_mut(tracker_entity)
-        else {
-            continue;
-        };
+//  attacking units stay put until their target dies
+type TrackingFilter = (With<Bubble>, Without<AttackOccurance>);
 
-        let Ok(target_transform) = target_query.get(target_entity) else {
+fn tracking(
+    mut tracker_query: Query<(&GlobalTransform, &mut Acceleration, &CurrentTarget), TrackingFilter>,
+    target_query: Query<&GlobalTransform, With<Target>>,
+) {
+    for (tracker_transform, mut acceleration, current_target) in tracker_query.iter_mut() {
+        let Ok(target_transform) = target_query.get(current_target.entity) else {
             continue;
         };
 
@@ -192,3 +242,22 @@ fn tracking<T: Component>(
         acceleration.value = direction * BUBBLE_ACCELERATION_RATE / distance;
     }
 }
+
+/// Bubbles that lost their target stop homing in on where it was.
+fn stop_tracking(
+    mut target_lost_reader: EventReader<TargetLost>,
+    mut tracker_query: Query<(&mut Acceleration, Option<&CurrentTarget>), With<Bubble>>,
+) {
+    for event in target_lost_reader.read() {
+        let Ok((mut acceleration, current_target)) = tracker_query.get_mut(event.tracker) else {
+            continue;
+        };
+
+        //  switching targets, rather than losing sight of everything
+        if current_target.is_some_and(|current_target| current_target.entity != event.target) {
+            continue;
+        }
+
+        acceleration.value = Vec3::ZERO;
+    }
+}
@@ -1,37 +1,44 @@
 use bevy::{
+    ecs::query::QueryData,
     prelude::*,
-    utils::hashbrown::{hash_map::Entry, HashMap},
+    transform::systems::{propagate_transforms, sync_simple_transforms},
+    utils::hashbrown::{HashMap, HashSet},
 };
 
 use crate::{
-    bubble::{Bubble, BubbleSpawner},
-    footman::Footman,
+    damage::{DamageEvent, DamageKind},
     group::Group,
-    health::Health,
     movement::Velocity,
+    projectile::Projectile,
     schedule::InGameSet,
-    tower::Tower,
+    shape::{contact, time_of_impact, ColliderShape},
+    spatial::{update_spatial_hash, SpatialHash},
 };
 
 const COLLISION_BUFFER: f32 = 2.0;
+const CCD_SLOP: f32 = 1.0;
+const SPATIAL_HASH_CELL_SIZE: f32 = 64.0;
 
 pub struct CollisionsPlugin;
 
 impl Plugin for CollisionsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            collision_detection.in_set(InGameSet::CollisionDetection),
+            FixedUpdate,
+            (
+                //  this tick's movement has only reached `Transform` so far
+                (sync_simple_transforms, propagate_transforms),
+                update_spatial_hash,
+                collision_detection,
+                rewind_fast_movers,
+            )
+                .chain()
+                .in_set(InGameSet::CollisionDetection),
         )
         .add_systems(
-            Update,
+            FixedUpdate,
             (
-                (
-                    handle_collisions::<Tower>,
-                    handle_collisions::<Footman>,
-                    handle_collisions::<BubbleSpawner>,
-                    handle_collisions::<Bubble>,
-                ),
+                handle_collisions,
                 (
                     apply_collision_damage,
                     update_solid_collisions,
@@ -41,21 +48,25 @@ impl Plugin for CollisionsPlugin {
                 .chain()
                 .in_set(InGameSet::EntityUpdates),
         )
-        .insert_resource(CollisionRecords::new(HashMap::new()))
-        .add_event::<CollisionEvent>();
+        .insert_resource(CollisionRecords::new(HashSet::new()))
+        .insert_resource(SpatialHash::new(SPATIAL_HASH_CELL_SIZE))
+        .add_event::<CollisionEvent>()
+        .add_event::<CollisionStarted>()
+        .add_event::<CollisionOngoing>()
+        .add_event::<CollisionEnded>();
     }
 }
 
 #[derive(Component, Debug)]
 pub struct Collider {
-    pub radius: f32,
+    pub shape: ColliderShape,
     pub colliding_entities: Vec<Entity>,
 }
 
 impl Collider {
-    pub fn new(radius: f32) -> Self {
+    pub fn new(shape: ColliderShape) -> Self {
         Self {
-            radius,
+            shape,
             colliding_entities: vec![],
         }
     }
@@ -64,14 +75,24 @@ impl Collider {
 #[derive(Component, Debug)]
 pub struct CollisionDamage {
     pub amount: f32,
+    pub kind: DamageKind,
+    pub mode: CollisionDamageMode,
 }
 
 impl CollisionDamage {
-    pub fn new(amount: f32) -> Self {
-        Self { amount }
+    pub fn new(amount: f32, kind: DamageKind, mode: CollisionDamageMode) -> Self {
+        Self { amount, kind, mode }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionDamageMode {
+    /// Damage is applied once, when the collision starts.
+    OnEnter,
+    /// Damage is applied on every tick the collision lasts.
+    PerTick,
+}
+
 #[derive(Component, Debug)]
 pub struct Bounce {
     pub value: f32,
@@ -83,13 +104,84 @@ impl Bounce {
     }
 }
 
+/// How heavy a body is when collisions push it around.
+/// Bodies without a `Mass` (and not `Static`) weigh 1.
+#[derive(Component, Debug)]
+pub struct Mass {
+    pub value: f32,
+}
+
+impl Mass {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+/// Marks a body that is never moved by collisions, such as buildings.
+#[derive(Component, Debug)]
+pub struct Static;
+
+/// Flags a body that can travel further than its own size in one frame.
+/// Its whole path since the last detection pass is swept for contacts, so it cannot tunnel.
+#[derive(Component, Debug, Default)]
+pub struct FastMover {
+    pub previous_position: Option<Vec2>,
+    /// Where the body first touched something along its path, if it passed through anything.
+    pub impact_position: Option<Vec2>,
+}
+
+impl FastMover {
+    /// Start and end of the path travelled since the last detection pass.
+    pub fn path(&self, position: Vec2) -> (Vec2, Vec2) {
+        (self.previous_position.unwrap_or(position), position)
+    }
+}
+
+/// A circle containing everything a body of `radius` touched along its path.
+pub fn swept_bounds(path: (Vec2, Vec2), radius: f32) -> (Vec2, f32) {
+    (
+        (path.0 + path.1) / 2.0,
+        path.0.distance(path.1) / 2.0 + radius,
+    )
+}
+
+/// Marks a collider that only reports overlaps, without any physical response.
+#[derive(Component, Debug)]
+pub struct Sensor;
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct CollisionBody {
+    global_transform: &'static GlobalTransform,
+    collider: &'static Collider,
+    transform: &'static mut Transform,
+    velocity: Option<&'static mut Velocity>,
+    bounce: Option<&'static Bounce>,
+    mass: Option<&'static Mass>,
+    is_static: Has<Static>,
+}
+
+impl CollisionBodyItem<'_> {
+    fn inverse_mass(&self) -> f32 {
+        if self.is_static {
+            return 0.0;
+        }
+
+        match self.mass {
+            Some(mass) if mass.value > 0.0 => 1.0 / mass.value,
+            _ => 1.0,
+        }
+    }
+}
+
+/// The `(entity, colliding_entity)` pairs that were in contact on the last detection pass.
 #[derive(Resource, Debug)]
 pub struct CollisionRecords {
-    pub value: HashMap<(Entity, Entity), bool>,
+    pub value: HashSet<(Entity, Entity)>,
 }
 
 impl CollisionRecords {
-    pub fn new(value: HashMap<(Entity, Entity), bool>) -> Self {
+    pub fn new(value: HashSet<(Entity, Entity)>) -> Self {
         Self { value }
     }
 }
@@ -109,6 +201,54 @@ impl CollisionEvent {
     }
 }
 
+/// Sent on the first detection pass a pair is found in contact.
+#[derive(Event, Debug)]
+pub struct CollisionStarted {
+    pub entity: Entity,
+    pub colliding_entity: Entity,
+}
+
+impl CollisionStarted {
+    pub fn new(entity: Entity, colliding_entity: Entity) -> Self {
+        Self {
+            entity,
+            colliding_entity,
+        }
+    }
+}
+
+/// Sent on every following detection pass the pair stays in contact.
+#[derive(Event, Debug)]
+pub struct CollisionOngoing {
+    pub entity: Entity,
+    pub colliding_entity: Entity,
+}
+
+impl CollisionOngoing {
+    pub fn new(entity: Entity, colliding_entity: Entity) -> Self {
+        Self {
+            entity,
+            colliding_entity,
+        }
+    }
+}
+
+/// Sent once the pair separates, or when either entity loses its collider or despawns.
+#[derive(Event, Debug)]
+pub struct CollisionEnded {
+    pub entity: Entity,
+    pub colliding_entity: Entity,
+}
+
+impl CollisionEnded {
+    pub fn new(entity: Entity, colliding_entity: Entity) -> Self {
+        Self {
+            entity,
+            colliding_entity,
+        }
+    }
+}
+
 /// Pairwise collision filtering using bit masks.
 ///
 /// This filtering method is based on two 32-bit values:
@@ -145,14 +285,37 @@ impl CollisionGroups {
 
 fn collision_detection(
     mut query: Query<(Entity, &CollisionGroups, &GlobalTransform, &mut Collider)>,
+    mut fast_movers: Query<&mut FastMover>,
+    spatial_hash: Res<SpatialHash>,
     mut collision_records: ResMut<CollisionRecords>,
+    mut collision_started_writer: EventWriter<CollisionStarted>,
+    mut collision_ongoing_writer: EventWriter<CollisionOngoing>,
+    mut collision_ended_writer: EventWriter<CollisionEnded>,
 ) {
     let mut colliding_entities: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut impacts: HashMap<Entity, (f32, Vec2)> = HashMap::new();
     // let mut filtered_entities: HashMap<Entity, Vec<Entity>> = HashMap::new();
 
     //  phase 1: detect collisions
     for (entity_a, groups_a, transform_a, collider_a) in query.iter() {
-        for (entity_b, groups_b, transform_b, collider_b) in query.iter() {
+        let path_a = match fast_movers.get(entity_a) {
+            Ok(fast_mover) => fast_mover.path(transform_a.translation().truncate()),
+            Err(_) => (
+                transform_a.translation().truncate(),
+                transform_a.translation().truncate(),
+            ),
+        };
+
+        //  broadphase: only test colliders sharing a cell with this one
+        let (bounds_center, bounds_radius) =
+            swept_bounds(path_a, collider_a.shape.bounding_radius());
+        let nearby_entities = spatial_hash.query(bounds_center, bounds_radius);
+
+        for entity_b in nearby_entities {
+            let Ok((_, groups_b, transform_b, collider_b)) = query.get(entity_b) else {
+                continue;
+            };
+
             //  cannot collide with self
             if entity_a == entity_b {
                 continue;
@@ -163,34 +326,71 @@ fn collision_detection(
                 continue;
             }
 
-            //  next, check if a collision would occur
-            let distance = transform_a
-                .translation()
-                .distance(transform_b.translation());
-
             //  here for weird transform::Zero bug
-            if distance == 0.0 {
+            if transform_a.translation() == transform_b.translation() {
                 continue;
             }
 
-            if distance < (collider_a.radius + collider_b.radius) {
-                //  check for immediately previous collision
-                match collision_records.value.entry((entity_a, entity_b)) {
-                    Entry::Occupied(_) => {
-                        let Some(_) = collision_records.value.remove(&(entity_a, entity_b)) else {
-                            continue;
-                        };
-                        continue;
-                    }
-                    _ => {
-                        collision_records.value.insert((entity_a, entity_b), true);
-                    }
-                }
-
+            //  next, check if a collision would occur
+            if contact(
+                &collider_a.shape,
+                transform_a,
+                &collider_b.shape,
+                transform_b,
+            )
+            .is_some()
+            {
                 colliding_entities
                     .entry(entity_a)
                     .or_insert_with(Vec::new)
                     .push(entity_b);
+                continue;
+            }
+
+            //  finally, check if a fast mover passed through the other body since the last pass
+            let fast_mover_b = fast_movers.get(entity_b).ok();
+            if fast_movers.get(entity_a).is_err() && fast_mover_b.is_none() {
+                continue;
+            }
+
+            let position_b = transform_b.translation().truncate();
+            let path_b = match fast_mover_b {
+                Some(fast_mover) => fast_mover.path(position_b),
+                None => (position_b, position_b),
+            };
+
+            //  sweep a relative to b, as if b was resting at the origin
+            let Some(toi) = time_of_impact(
+                path_a.0 - path_b.0,
+                path_a.1 - path_b.1,
+                collider_a.shape.bounding_radius() + collider_b.shape.bounding_radius(),
+            ) else {
+                continue;
+            };
+
+            colliding_entities
+                .entry(entity_a)
+                .or_insert_with(Vec::new)
+                .push(entity_b);
+
+            //  rewind to the impact, nudged slightly into each other so the response sees it
+            let impact_a = path_a.0.lerp(path_a.1, toi);
+            let impact_b = path_b.0.lerp(path_b.1, toi);
+            let nudge = (impact_b - impact_a).normalize_or_zero() * CCD_SLOP;
+
+            for (entity, impact_position) in
+                [(entity_a, impact_a + nudge), (entity_b, impact_b - nudge)]
+            {
+                if fast_movers.get(entity).is_err() {
+                    continue;
+                }
+
+                match impacts.get(&entity) {
+                    Some(&(earliest_toi, _)) if earliest_toi <= toi => {}
+                    _ => {
+                        impacts.insert(entity, (toi, impact_position));
+                    }
+                }
             }
         }
     }
@@ -204,138 +404,514 @@ fn collision_detection(
                 .extend(collisions.iter().copied());
         }
     }
+
+    for (&entity, &(_, impact_position)) in impacts.iter() {
+        if let Ok(mut fast_mover) = fast_movers.get_mut(entity) {
+            fast_mover.impact_position = Some(impact_position);
+        }
+    }
+
+    //  phase 3: compare against the last pass to find contact state changes
+    let mut records: HashSet<(Entity, Entity)> = HashSet::new();
+
+    for (&entity, collisions) in colliding_entities.iter() {
+        for &colliding_entity in collisions.iter() {
+            if collision_records
+                .value
+                .contains(&(entity, colliding_entity))
+            {
+                collision_ongoing_writer.send(CollisionOngoing::new(entity, colliding_entity));
+            } else {
+                collision_started_writer.send(CollisionStarted::new(entity, colliding_entity));
+            }
+
+            records.insert((entity, colliding_entity));
+        }
+    }
+
+    //  despawned entities simply drop out of the new records
+    for &(entity, colliding_entity) in collision_records.value.difference(&records) {
+        collision_ended_writer.send(CollisionEnded::new(entity, colliding_entity));
+    }
+
+    collision_records.value = records;
 }
 
-fn handle_collisions<T: Component>(
+fn rewind_fast_movers(mut query: Query<(&GlobalTransform, &mut Transform, &mut FastMover)>) {
+    for (global_transform, mut transform, mut fast_mover) in query.iter_mut() {
+        let position = global_transform.translation().truncate();
+
+        match fast_mover.impact_position.take() {
+            Some(impact_position) => {
+                //  pull the body back to where it first touched
+                transform.translation = impact_position.extend(transform.translation.z);
+                fast_mover.previous_position = Some(impact_position);
+            }
+            None => {
+                fast_mover.previous_position = Some(position);
+            }
+        }
+    }
+}
+
+fn handle_collisions(
     mut collision_event_writer: EventWriter<CollisionEvent>,
-    query: Query<(Entity, &Collider), With<T>>,
+    query: Query<(Entity, &Collider)>,
 ) {
     for (entity, collider) in query.iter() {
         for &colliding_entity in collider.colliding_entities.iter() {
-            //  entity colliding with another entity of the same type
-            if query.get(colliding_entity).is_ok() {
-                continue;
-            }
-
             collision_event_writer.send(CollisionEvent::new(entity, colliding_entity));
         }
     }
 }
 
+//  solid bodies only push each other around
+type CollisionAttackerFilter = Or<(With<Bounce>, With<Sensor>)>;
+
 pub fn apply_collision_damage(
-    mut collision_event_reader: EventReader<CollisionEvent>,
-    mut attacked_query: Query<&mut Health>,
-    attacker_query: Query<&CollisionDamage, With<Bounce>>,
+    mut collision_started_reader: EventReader<CollisionStarted>,
+    mut collision_ongoing_reader: EventReader<CollisionOngoing>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+    mut attacker_query: Query<(&CollisionDamage, Option<&mut Projectile>), CollisionAttackerFilter>,
 ) {
-    for &CollisionEvent {
-        entity,
-        colliding_entity,
-    } in collision_event_reader.read()
-    {
-        let Ok(mut health) = attacked_query.get_mut(entity) else {
+    let started = collision_started_reader
+        .read()
+        .map(|event| (event.entity, event.colliding_entity, true));
+    let ongoing = collision_ongoing_reader
+        .read()
+        .map(|event| (event.entity, event.colliding_entity, false));
+
+    for (entity, colliding_entity, is_start) in started.chain(ongoing) {
+        let Ok((collision_damage, projectile)) = attacker_query.get_mut(colliding_entity) else {
             continue;
         };
 
-        let Ok(collision_damage) = attacker_query.get(colliding_entity) else {
+        if !is_start && collision_damage.mode == CollisionDamageMode::OnEnter {
             continue;
-        };
+        }
+
+        let mut source = colliding_entity;
+
+        //  a projectile may touch several bodies on the same pass, only the first one is hit
+        if let Some(mut projectile) = projectile {
+            if projectile.is_spent {
+                continue;
+            }
+
+            projectile.is_spent = true;
+            //  and it hits on behalf of whoever fired it
+            source = projectile.owner;
+        }
 
-        health.value -= collision_damage.amount;
+        damage_event_writer.send(DamageEvent::new(
+            source,
+            entity,
+            collision_damage.amount,
+            collision_damage.kind,
+        ));
     }
 }
 
 pub fn update_solid_collisions(
     mut collision_event_reader: EventReader<CollisionEvent>,
-    attacked_query: Query<(&GlobalTransform, &Collider)>,
-    mut attacker_query: Query<(&GlobalTransform, &Collider, &mut Transform), Without<Bounce>>,
+    mut query: Query<CollisionBody, (Without<Bounce>, Without<Sensor>)>,
 ) {
+    let mut resolved_pairs: HashSet<(Entity, Entity)> = HashSet::new();
+
     for &CollisionEvent {
         entity,
         colliding_entity,
     } in collision_event_reader.read()
     {
-        let Ok((attacked_transform, attacked_collider)) = attacked_query.get(entity) else {
+        //  both directions of a pair may be reported, only resolve it once
+        if !resolved_pairs.insert(unordered_pair(entity, colliding_entity)) {
             continue;
-        };
+        }
 
-        let Ok((attacker_global_transform, attacker_collider, mut attacker_transform)) =
-            attacker_query.get_mut(colliding_entity)
-        else {
+        let Ok([mut body_a, mut body_b]) = query.get_many_mut([entity, colliding_entity]) else {
             continue;
         };
 
         //  0: gather variables
-        let planar_transform = Transform::from_xyz(
-            attacked_transform.translation().x,
-            attacked_transform.translation().y,
-            attacker_global_transform.translation().z,
-        );
-        let deflection_vec =
-            (attacker_global_transform.translation() - planar_transform.translation).normalize();
-        let required_distance: f32 = attacked_collider.radius + attacker_collider.radius;
-        let current_distance: f32 = attacker_global_transform
-            .translation()
-            .distance(planar_transform.translation);
-        let adjusted_distance = required_distance - current_distance;
+        let Some(contact) = contact(
+            &body_a.collider.shape,
+            body_a.global_transform,
+            &body_b.collider.shape,
+            body_b.global_transform,
+        ) else {
+            continue;
+        };
+        let deflection_vec = contact.normal.extend(0.0);
+        let inverse_mass_a = body_a.inverse_mass();
+        let inverse_mass_b = body_b.inverse_mass();
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+
+        if inverse_mass_sum == 0.0 {
+            continue;
+        }
 
-        //  1: "shift" the attacker off of the attacked to ensure no overlap
-        attacker_transform.translation += deflection_vec * adjusted_distance;
+        //  1: "shift" both bodies apart, the lighter one moving further
+        let separation = deflection_vec * contact.depth / inverse_mass_sum;
+        body_a.transform.translation -= separation * inverse_mass_a;
+        body_b.transform.translation += separation * inverse_mass_b;
     }
 }
 
 pub fn update_bouncy_collisions(
     mut collision_event_reader: EventReader<CollisionEvent>,
-    attacked_query: Query<(&GlobalTransform, &Collider)>,
-    mut attacker_query: Query<
-        (
-            &GlobalTransform,
-            &Collider,
-            &mut Transform,
-            &mut Velocity,
-            &Bounce,
-        ),
-        With<Bounce>,
-    >,
+    mut query: Query<CollisionBody, Without<Sensor>>,
 ) {
+    let mut resolved_pairs: HashSet<(Entity, Entity)> = HashSet::new();
+
     for &CollisionEvent {
         entity,
         colliding_entity,
     } in collision_event_reader.read()
     {
-        let Ok((attacked_transform, attacked_collider)) = attacked_query.get(entity) else {
+        //  both directions of a pair may be reported, only resolve it once
+        if !resolved_pairs.insert(unordered_pair(entity, colliding_entity)) {
             continue;
-        };
+        }
 
-        let Ok((
-            attacker_global_transform,
-            attacker_collider,
-            mut attacker_transform,
-            mut attacker_velocity,
-            bounce,
-        )) = attacker_query.get_mut(colliding_entity)
-        else {
+        let Ok([mut body_a, mut body_b]) = query.get_many_mut([entity, colliding_entity]) else {
             continue;
         };
 
+        //  pairs without any bounce are handled by the solid collisions
+        let restitution = match (body_a.bounce, body_b.bounce) {
+            (Some(bounce_a), Some(bounce_b)) => bounce_a.value.max(bounce_b.value),
+            (Some(bounce), None) | (None, Some(bounce)) => bounce.value,
+            (None, None) => continue,
+        };
+
         //  0: gather variables
-        let planar_transform = Transform::from_xyz(
-            attacked_transform.translation().x,
-            attacked_transform.translation().y,
-            attacker_global_transform.translation().z,
+        let Some(contact) = contact(
+            &body_a.collider.shape,
+            body_a.global_transform,
+            &body_b.collider.shape,
+            body_b.global_transform,
+        ) else {
+            continue;
+        };
+        let deflection_vec = contact.normal.extend(0.0);
+        let inverse_mass_a = body_a.inverse_mass();
+        let inverse_mass_b = body_b.inverse_mass();
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+
+        if inverse_mass_sum == 0.0 {
+            continue;
+        }
+
+        //  1: "shift" both bodies apart, the lighter one moving further
+        let separation = deflection_vec * (contact.depth + COLLISION_BUFFER) / inverse_mass_sum;
+        body_a.transform.translation -= separation * inverse_mass_a;
+        body_b.transform.translation += separation * inverse_mass_b;
+
+        //  2: "bounce" the bodies off each other with an impulse along the deflection
+        //  bodies without a velocity cannot take any of the impulse
+        let impulse_mass_a = body_a.velocity.as_ref().map_or(0.0, |_| inverse_mass_a);
+        let impulse_mass_b = body_b.velocity.as_ref().map_or(0.0, |_| inverse_mass_b);
+        let impulse_mass_sum = impulse_mass_a + impulse_mass_b;
+
+        if impulse_mass_sum == 0.0 {
+            continue;
+        }
+
+        let velocity_a = body_a.velocity.as_ref().map_or(Vec3::ZERO, |v| v.value);
+        let velocity_b = body_b.velocity.as_ref().map_or(Vec3::ZERO, |v| v.value);
+        let approach_speed = (velocity_b - velocity_a).dot(deflection_vec);
+
+        //  already moving apart
+        if approach_speed >= 0.0 {
+            continue;
+        }
+
+        let impulse = deflection_vec * -(1. + restitution) * approach_speed / impulse_mass_sum;
+
+        if let Some(velocity) = body_a.velocity.as_mut() {
+            velocity.value -= impulse * impulse_mass_a;
+        }
+        if let Some(velocity) = body_b.velocity.as_mut() {
+            velocity.value += impulse * impulse_mass_b;
+        }
+    }
+}
+
+fn unordered_pair(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+    use crate::{
+        bubble::{Bubble, BubbleSpawner},
+        damage::DamagePlugin,
+        footman::Footman,
+        health::Health,
+        schedule::test_app,
+    };
+
+    fn read<T: Event>(app: &App, reader: &mut ManualEventReader<T>) -> usize {
+        reader.read(app.world.resource::<Events<T>>()).count()
+    }
+
+    #[test]
+    fn contact_states_follow_the_overlap() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        app.world.spawn((
+            Collider::new(ColliderShape::Circle { radius: 10.0 }),
+            CollisionGroups::new(Group::ALLY, Group::NONE),
+            GlobalTransform::from_xyz(0.0, 0.0, 0.0),
+        ));
+        let other = app
+            .world
+            .spawn((
+                Collider::new(ColliderShape::Circle { radius: 10.0 }),
+                CollisionGroups::new(Group::ENEMY, Group::ALLY),
+                GlobalTransform::from_xyz(15.0, 0.0, 0.0),
+            ))
+            .id();
+
+        let mut started = ManualEventReader::<CollisionStarted>::default();
+        let mut ongoing = ManualEventReader::<CollisionOngoing>::default();
+        let mut ended = ManualEventReader::<CollisionEnded>::default();
+
+        app.update();
+        assert_eq!(read(&app, &mut started), 1);
+
+        app.update();
+        app.update();
+        assert_eq!(read(&app, &mut ongoing), 2);
+        assert_eq!(read(&app, &mut ended), 0);
+
+        app.world
+            .entity_mut(other)
+            .insert(GlobalTransform::from_xyz(40.0, 0.0, 0.0));
+        app.update();
+        assert_eq!(read(&app, &mut ended), 1);
+
+        app.world
+            .entity_mut(other)
+            .insert(GlobalTransform::from_xyz(15.0, 0.0, 0.0));
+        app.update();
+        assert_eq!(read(&app, &mut started), 1);
+
+        app.world.despawn(other);
+        app.update();
+        assert_eq!(read(&app, &mut ended), 1);
+        assert_eq!(read(&app, &mut ongoing), 0);
+    }
+
+    #[test]
+    fn damage_mode_decides_how_often_a_contact_hurts() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin));
+
+        let target = app
+            .world
+            .spawn((Health::new(100.0), TransformBundle::default()))
+            .id();
+        let on_enter = app
+            .world
+            .spawn((
+                CollisionDamage::new(5.0, DamageKind::Pierce, CollisionDamageMode::OnEnter),
+                Bounce::new(0.0),
+            ))
+            .id();
+        let per_tick = app
+            .world
+            .spawn((
+                CollisionDamage::new(1.0, DamageKind::Pierce, CollisionDamageMode::PerTick),
+                Bounce::new(0.0),
+            ))
+            .id();
+
+        app.world
+            .send_event(CollisionStarted::new(target, on_enter));
+        app.world
+            .send_event(CollisionStarted::new(target, per_tick));
+        app.update();
+
+        for _ in 0..3 {
+            app.world
+                .send_event(CollisionOngoing::new(target, on_enter));
+            app.world
+                .send_event(CollisionOngoing::new(target, per_tick));
+            app.update();
+        }
+        //  damage sent after the entity updates lands on the next frame
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(target).unwrap().current, 91.0);
+    }
+
+    fn footman(app: &mut App, x: f32) -> Entity {
+        app.world
+            .spawn((
+                Footman,
+                Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                CollisionGroups::new(
+                    Group::ENEMY | Group::NPC,
+                    Group::ALLY | Group::PLAYER | Group::NPC,
+                ),
+                Mass::new(4.0),
+                Velocity::new(Vec3::ZERO),
+                Transform::from_xyz(x, 0.0, 0.0),
+                GlobalTransform::from_xyz(x, 0.0, 0.0),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn footmen_shove_each_other_apart() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let footman_a = footman(&mut app, 0.0);
+        let footman_b = footman(&mut app, 20.0);
+
+        //  one pass to detect the overlap, one to resolve it
+        app.update();
+        app.update();
+
+        let position_a = app.world.get::<Transform>(footman_a).unwrap().translation;
+        let position_b = app.world.get::<Transform>(footman_b).unwrap().translation;
+        assert!((position_a.x + 6.0).abs() < 1e-4);
+        assert!((position_b.x - 26.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bubbles_knock_footmen_back() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let footman = footman(&mut app, 0.0);
+        let bubble = app
+            .world
+            .spawn((
+                Bubble {
+                    lifetime: Timer::from_seconds(1.0, TimerMode::Once),
+                },
+                Collider::new(ColliderShape::Circle { radius: 8.0 }),
+                CollisionGroups::new(Group::ALLY, Group::ENEMY),
+                Bounce::new(0.9),
+                Mass::new(1.0),
+                Velocity::new(Vec3::new(-100.0, 0.0, 0.0)),
+                Transform::from_xyz(20.0, 0.0, 0.0),
+                GlobalTransform::from_xyz(20.0, 0.0, 0.0),
+            ))
+            .id();
+
+        app.update();
+        app.update();
+
+        //  the lighter bubble takes most of the impulse
+        let footman_velocity = app.world.get::<Velocity>(footman).unwrap().value;
+        let bubble_velocity = app.world.get::<Velocity>(bubble).unwrap().value;
+        assert!(footman_velocity.x < 0.0);
+        assert!(bubble_velocity.x > -footman_velocity.x);
+    }
+
+    #[test]
+    fn static_bodies_stay_put() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let footman = footman(&mut app, 0.0);
+        let spawner = app
+            .world
+            .spawn((
+                BubbleSpawner {
+                    spawn_rate: Timer::from_seconds(1.0, TimerMode::Repeating),
+                },
+                Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                Static,
+                Transform::from_xyz(20.0, 0.0, 0.0),
+                GlobalTransform::from_xyz(20.0, 0.0, 0.0),
+            ))
+            .id();
+
+        app.update();
+        app.update();
+
+        let footman_position = app.world.get::<Transform>(footman).unwrap().translation;
+        let spawner_position = app.world.get::<Transform>(spawner).unwrap().translation;
+        assert!((footman_position.x + 12.0).abs() < 1e-4);
+        assert_eq!(spawner_position.x, 20.0);
+    }
+
+    #[test]
+    fn fast_bubble_cannot_tunnel_through_footman() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let footman = app
+            .world
+            .spawn((
+                Footman,
+                TransformBundle::default(),
+                Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                CollisionGroups::new(
+                    Group::ENEMY | Group::NPC,
+                    Group::ALLY | Group::PLAYER | Group::NPC,
+                ),
+                Mass::new(4.0),
+                Velocity::new(Vec3::ZERO),
+            ))
+            .id();
+        let bubble = app
+            .world
+            .spawn((
+                Bubble {
+                    lifetime: Timer::from_seconds(1.0, TimerMode::Once),
+                },
+                TransformBundle::from_transform(Transform::from_xyz(-60.0, 0.0, 0.0)),
+                Collider::new(ColliderShape::Circle { radius: 8.0 }),
+                CollisionGroups::new(Group::ALLY, Group::ENEMY),
+                Bounce::new(0.9),
+                Mass::new(1.0),
+                FastMover::default(),
+                Velocity::new(Vec3::new(6000.0, 0.0, 0.0)),
+            ))
+            .id();
+
+        app.update();
+
+        //  a hundred units in a single frame, ending clear on the other side
+        app.world
+            .get_mut::<Transform>(bubble)
+            .unwrap()
+            .translation
+            .x = 40.0;
+        app.update();
+
+        let position = app.world.get::<Transform>(bubble).unwrap().translation;
+        assert!(
+            (-24.0..-16.0).contains(&position.x),
+            "bubble should be rewound to the impact, not {position}"
         );
-        let deflection_vec =
-            (attacker_global_transform.translation() - planar_transform.translation).normalize();
-        let required_distance: f32 = attacked_collider.radius + attacker_collider.radius;
-        let current_distance: f32 = attacker_global_transform
-            .translation()
-            .distance(planar_transform.translation);
-        let adjusted_distance = required_distance - current_distance;
-
-        //  1: "shift" the attacker off of the attacked to ensure no overlap
-        attacker_transform.translation += deflection_vec * (adjusted_distance + COLLISION_BUFFER);
-
-        //  2: "bounce" the attacker off the attacked
-        let radial_velocity = deflection_vec.dot(attacker_velocity.value) * deflection_vec;
-        attacker_velocity.value += -(1. + bounce.value) * radial_velocity;
+        assert!(app
+            .world
+            .get::<Collider>(bubble)
+            .unwrap()
+            .colliding_entities
+            .contains(&footman));
+
+        //  the next frame bounces it back the way it came
+        app.update();
+
+        assert!(app.world.get::<Velocity>(bubble).unwrap().value.x < 0.0);
+        assert!(app.world.get::<Velocity>(footman).unwrap().value.x > 0.0);
+        assert!(app.world.get::<Transform>(bubble).unwrap().translation.x < -24.0);
     }
 }
@@ -0,0 +1,546 @@
+use bevy::{ecs::query::QueryData, prelude::*, utils::hashbrown::HashSet};
+
+use crate::{group::Group, health::Health, schedule::InGameSet, spatial::SpatialQuery};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (recharge_shields, expire_invulnerability),
+                apply_area_damage,
+                apply_damage,
+            )
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .add_event::<AreaDamageEvent>()
+        .add_event::<DamageEvent>()
+        .add_event::<DamageDealt>()
+        .add_event::<EntityDied>();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    Pierce,
+    /// Ignores `Armor`.
+    Magic,
+}
+
+/// Every hit goes through this event, so the receiver's `Armor` and `Resistances` apply.
+#[derive(Event, Debug)]
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+    /// Damage over time, rather than a hit.
+    pub is_periodic: bool,
+}
+
+impl DamageEvent {
+    pub fn new(source: Entity, target: Entity, amount: f32, kind: DamageKind) -> Self {
+        Self {
+            source,
+            target,
+            amount,
+            kind,
+            is_periodic: false,
+        }
+    }
+
+    pub fn periodic(source: Entity, target: Entity, amount: f32, kind: DamageKind) -> Self {
+        Self {
+            is_periodic: true,
+            ..Self::new(source, target, amount, kind)
+        }
+    }
+}
+
+/// A `DamageEvent` that got through, carrying the health it actually took away.
+/// Hits that armor, shields or invulnerability fully stop are not sent.
+#[derive(Event, Debug)]
+pub struct DamageDealt(pub DamageEvent);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    Damage(DamageKind),
+    /// The entity's lifetime ran out.
+    Expired,
+    /// Health ran out without a `DamageEvent`.
+    Unknown,
+}
+
+/// Sent exactly once for every death, before the entity is despawned.
+#[derive(Event, Debug)]
+pub struct EntityDied {
+    pub entity: Entity,
+    /// The source of the killing blow, if anything dealt it.
+    pub killer: Option<Entity>,
+    pub cause: DeathCause,
+    pub position: Vec2,
+}
+
+impl EntityDied {
+    pub fn new(entity: Entity, killer: Option<Entity>, cause: DeathCause, position: Vec2) -> Self {
+        Self {
+            entity,
+            killer,
+            cause,
+            position,
+        }
+    }
+}
+
+/// Flat reduction of every hit that is not magic.
+#[derive(Component, Debug)]
+pub struct Armor {
+    pub value: f32,
+}
+
+impl Armor {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+/// Share (0..=1) of each damage kind that is shrugged off, applied before `Armor`.
+#[derive(Component, Debug, Default)]
+pub struct Resistances {
+    pub pierce: f32,
+    pub magic: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Pierce => self.pierce,
+            DamageKind::Magic => self.magic,
+        }
+    }
+}
+
+/// Absorbs damage before `Health` does. Recharges once no damage was taken for a while.
+#[derive(Component, Debug)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    /// Shield recovered per second while recharging.
+    pub recharge_rate: f32,
+    pub recharge_delay: Timer,
+}
+
+impl Shield {
+    /// Starts fully charged.
+    pub fn new(max: f32, recharge_rate: f32, recharge_delay: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            recharge_rate,
+            recharge_delay: Timer::from_seconds(recharge_delay, TimerMode::Once),
+        }
+    }
+
+    /// Takes as much of the damage as possible, returning what goes through.
+    pub fn absorb(&mut self, amount: f32) -> f32 {
+        self.recharge_delay.reset();
+
+        let absorbed = amount.min(self.current);
+        self.current -= absorbed;
+
+        amount - absorbed
+    }
+}
+
+/// Ignores all damage until the timer runs out.
+#[derive(Component, Debug)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
+
+impl Invulnerable {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+/// Makes the entity `Invulnerable` for `duration` seconds after every hit.
+#[derive(Component, Debug)]
+pub struct InvulnerableOnHit {
+    pub duration: f32,
+}
+
+impl InvulnerableOnHit {
+    pub fn new(duration: f32) -> Self {
+        Self { duration }
+    }
+}
+
+/// How area damage weakens from the center to the edge of its radius.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Falloff {
+    Linear,
+}
+
+impl Falloff {
+    /// Share of the damage dealt at `ratio` (0 at the center, 1 at the edge) of the radius.
+    pub fn scale(&self, ratio: f32) -> f32 {
+        let ratio = ratio.clamp(0.0, 1.0);
+
+        match self {
+            Falloff::Linear => 1.0 - ratio,
+        }
+    }
+}
+
+/// Damage dealt to everything in a circle when the entity goes off,
+/// for instance when it dies or when a projectile carrying it hits.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AreaDamage {
+    pub amount: f32,
+    pub kind: DamageKind,
+    pub radius: f32,
+    pub falloff: Falloff,
+    /// Only colliders with one of these `CollisionGroups` memberships are damaged.
+    pub filter: Group,
+}
+
+impl AreaDamage {
+    pub fn new(
+        amount: f32,
+        kind: DamageKind,
+        radius: f32,
+        falloff: Falloff,
+        filter: Group,
+    ) -> Self {
+        Self {
+            amount,
+            kind,
+            radius,
+            falloff,
+            filter,
+        }
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct AreaDamageEvent {
+    pub source: Entity,
+    pub position: Vec2,
+    pub area_damage: AreaDamage,
+}
+
+impl AreaDamageEvent {
+    pub fn new(source: Entity, position: Vec2, area_damage: AreaDamage) -> Self {
+        Self {
+            source,
+            position,
+            area_damage,
+        }
+    }
+}
+
+fn apply_area_damage(
+    mut area_damage_event_reader: EventReader<AreaDamageEvent>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+    target_query: Query<&GlobalTransform, With<Health>>,
+    spatial_query: SpatialQuery,
+) {
+    for &AreaDamageEvent {
+        source,
+        position,
+        area_damage,
+    } in area_damage_event_reader.read()
+    {
+        let entities =
+            spatial_query.overlap_circle(position, area_damage.radius, area_damage.filter);
+
+        for entity in entities {
+            if entity == source {
+                continue;
+            }
+
+            let Ok(transform) = target_query.get(entity) else {
+                continue;
+            };
+
+            let distance = position.distance(transform.translation().truncate());
+            let scale = area_damage.falloff.scale(distance / area_damage.radius);
+
+            damage_event_writer.send(DamageEvent::new(
+                source,
+                entity,
+                area_damage.amount * scale,
+                area_damage.kind,
+            ));
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct DamageReceiver {
+    health: &'static mut Health,
+    global_transform: &'static GlobalTransform,
+    armor: Option<&'static Armor>,
+    resistances: Option<&'static Resistances>,
+    shield: Option<&'static mut Shield>,
+    is_invulnerable: Has<Invulnerable>,
+    invulnerable_on_hit: Option<&'static InvulnerableOnHit>,
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_event_reader: EventReader<DamageEvent>,
+    mut target_query: Query<DamageReceiver>,
+    mut damage_dealt_writer: EventWriter<DamageDealt>,
+    mut entity_died_writer: EventWriter<EntityDied>,
+) {
+    //  invulnerability only starts next frame, but must already cover the hits that follow
+    let mut invulnerable_entities: HashSet<Entity> = HashSet::new();
+
+    for &DamageEvent {
+        source,
+        target,
+        amount,
+        kind,
+        is_periodic,
+    } in damage_event_reader.read()
+    {
+        let Ok(mut receiver) = target_query.get_mut(target) else {
+            continue;
+        };
+
+        //  already dead, waiting to be despawned
+        if receiver.health.current <= 0.0 {
+            continue;
+        }
+
+        if receiver.is_invulnerable || invulnerable_entities.contains(&target) {
+            continue;
+        }
+
+        let resistance = receiver.resistances.map_or(0.0, |r| r.get(kind));
+        let mut amount = amount * (1.0 - resistance.clamp(0.0, 1.0));
+
+        if kind != DamageKind::Magic {
+            amount -= receiver.armor.map_or(0.0, |armor| armor.value);
+        }
+
+        let mut amount = amount.max(0.0);
+
+        if amount == 0.0 {
+            continue;
+        }
+
+        //  the shield takes what it can first
+        if let Some(shield) = receiver.shield.as_mut() {
+            amount = shield.absorb(amount);
+        }
+
+        receiver.health.current -= amount;
+
+        if amount > 0.0 {
+            damage_dealt_writer.send(DamageDealt(DamageEvent {
+                source,
+                target,
+                amount,
+                kind,
+                is_periodic,
+            }));
+        }
+
+        if let Some(invulnerable_on_hit) = receiver.invulnerable_on_hit {
+            if !is_periodic {
+                invulnerable_entities.insert(target);
+                commands
+                    .entity(target)
+                    .insert(Invulnerable::new(invulnerable_on_hit.duration));
+            }
+        }
+
+        if receiver.health.current <= 0.0 {
+            entity_died_writer.send(EntityDied::new(
+                target,
+                Some(source),
+                DeathCause::Damage(kind),
+                receiver.global_transform.translation().truncate(),
+            ));
+        }
+    }
+}
+
+fn recharge_shields(mut query: Query<&mut Shield>, time: Res<Time>) {
+    for mut shield in query.iter_mut() {
+        shield.recharge_delay.tick(time.delta());
+
+        if shield.recharge_delay.finished() && shield.current < shield.max {
+            shield.current =
+                (shield.current + shield.recharge_rate * time.delta_seconds()).min(shield.max);
+        }
+    }
+}
+
+fn expire_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        invulnerable.timer.tick(time.delta());
+
+        if invulnerable.timer.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collisions::{Collider, CollisionGroups, CollisionsPlugin},
+        schedule::test_app,
+        shape::ColliderShape,
+    };
+
+    #[test]
+    fn area_damage_falls_off_and_respects_the_filter() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin));
+
+        let mut spawn = |x: f32, memberships: Group| {
+            app.world
+                .spawn((
+                    Health::new(10.0),
+                    Collider::new(ColliderShape::Circle { radius: 5.0 }),
+                    CollisionGroups::new(memberships, Group::NONE),
+                    TransformBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+                ))
+                .id()
+        };
+        let source = spawn(0.0, Group::ENEMY);
+        let near = spawn(10.0, Group::ENEMY);
+        let far = spawn(30.0, Group::ENEMY);
+        let ally = spawn(10.0, Group::ALLY);
+        let outside = spawn(60.0, Group::ENEMY);
+
+        //  let the broadphase pick everything up first
+        app.update();
+        app.world.send_event(AreaDamageEvent::new(
+            source,
+            Vec2::ZERO,
+            AreaDamage::new(8.0, DamageKind::Pierce, 40.0, Falloff::Linear, Group::ENEMY),
+        ));
+        app.update();
+
+        let health = |entity| app.world.get::<Health>(entity).unwrap().current;
+        assert_eq!(health(source), 10.0);
+        assert_eq!(health(near), 4.0);
+        assert_eq!(health(far), 8.0);
+        assert_eq!(health(ally), 10.0);
+        assert_eq!(health(outside), 10.0);
+    }
+
+    #[test]
+    fn armor_and_resistances_mitigate_by_kind() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin));
+
+        let source = app.world.spawn_empty().id();
+        let armored = app
+            .world
+            .spawn((
+                Health::new(20.0),
+                Armor::new(2.0),
+                TransformBundle::default(),
+            ))
+            .id();
+        let resistant = app
+            .world
+            .spawn((
+                Health::new(20.0),
+                Armor::new(2.0),
+                Resistances {
+                    pierce: 0.5,
+                    ..default()
+                },
+                TransformBundle::default(),
+            ))
+            .id();
+
+        for target in [armored, resistant] {
+            for kind in [DamageKind::Pierce, DamageKind::Magic] {
+                app.world
+                    .send_event(DamageEvent::new(source, target, 6.0, kind));
+            }
+        }
+        //  armor never heals
+        app.world
+            .send_event(DamageEvent::new(source, armored, 1.0, DamageKind::Pierce));
+        app.update();
+
+        let health = |entity| app.world.get::<Health>(entity).unwrap().current;
+        //  6 - 2 pierce, 6 magic through the armor
+        assert_eq!(health(armored), 10.0);
+        //  6 halved then - 2 pierce, 6 magic
+        assert_eq!(health(resistant), 13.0);
+    }
+
+    #[test]
+    fn shields_absorb_first_and_invulnerability_follows_a_hit() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin));
+
+        let source = app.world.spawn_empty().id();
+        let shielded = app
+            .world
+            .spawn((
+                Health::new(20.0),
+                Shield::new(5.0, 10.0, 1.0),
+                TransformBundle::default(),
+            ))
+            .id();
+        let flinching = app
+            .world
+            .spawn((
+                Health::new(20.0),
+                InvulnerableOnHit::new(0.5),
+                TransformBundle::default(),
+            ))
+            .id();
+
+        app.update();
+        for _ in 0..2 {
+            for target in [shielded, flinching] {
+                app.world
+                    .send_event(DamageEvent::new(source, target, 4.0, DamageKind::Pierce));
+            }
+        }
+        app.update();
+
+        let health = |app: &App, entity| app.world.get::<Health>(entity).unwrap().current;
+        let shield = |app: &App| app.world.get::<Shield>(shielded).unwrap().current;
+        //  the shield takes 5 of the 8
+        assert_eq!(health(&app, shielded), 17.0);
+        assert_eq!(shield(&app), 0.0);
+        //  the second hit lands while invulnerable
+        assert_eq!(health(&app, flinching), 16.0);
+
+        //  recharges only after the delay
+        for _ in 0..30 {
+            app.update();
+        }
+        assert_eq!(shield(&app), 0.0);
+        assert!(app.world.get::<Invulnerable>(flinching).is_none());
+        for _ in 0..60 {
+            app.update();
+        }
+        assert_eq!(shield(&app), 5.0);
+    }
+}
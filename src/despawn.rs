@@ -1,22 +1,180 @@
-use bevy::prelude::*;
+use bevy::{ecs::event::ManualEventReader, prelude::*, utils::HashSet};
 
-use crate::{health::Health, schedule::InGameSet};
+use crate::{
+    damage::{AreaDamage, AreaDamageEvent, DeathCause, EntityDied},
+    health::Health,
+    schedule::InGameSet,
+    Mana,
+};
 
 pub struct DespawnPlugin;
 
 impl Plugin for DespawnPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            despawn_dead_entities.in_set(InGameSet::DespawnEntities),
+            FixedUpdate,
+            (
+                report_unreported_deaths,
+                (detonate_on_death, pay_bounties),
+                despawn_dead_entities,
+            )
+                .chain()
+                .in_set(InGameSet::DespawnEntities),
         );
     }
 }
 
+/// Mana paid to the player for killing the entity.
+#[derive(Component, Debug)]
+pub struct Bounty {
+    pub mana: f32,
+}
+
+impl Bounty {
+    pub fn new(mana: f32) -> Self {
+        Self { mana }
+    }
+}
+
+//  deaths are reported where they are dealt, this catches health that ran out any other way
+fn report_unreported_deaths(
+    query: Query<(Entity, &Health, &GlobalTransform)>,
+    mut entity_died_events: ResMut<Events<EntityDied>>,
+    mut reported_reader: Local<ManualEventReader<EntityDied>>,
+) {
+    let reported: HashSet<Entity> = reported_reader
+        .read(&entity_died_events)
+        .map(|event| event.entity)
+        .collect();
+
+    for (entity, health, transform) in query.iter() {
+        if health.current <= 0.0 && !reported.contains(&entity) {
+            entity_died_events.send(EntityDied::new(
+                entity,
+                None,
+                DeathCause::Unknown,
+                transform.translation().truncate(),
+            ));
+        }
+    }
+}
+
+//  go off on death
+fn detonate_on_death(
+    mut entity_died_reader: EventReader<EntityDied>,
+    query: Query<&AreaDamage>,
+    mut area_damage_event_writer: EventWriter<AreaDamageEvent>,
+) {
+    for event in entity_died_reader.read() {
+        if let Ok(&area_damage) = query.get(event.entity) {
+            area_damage_event_writer.send(AreaDamageEvent::new(
+                event.entity,
+                event.position,
+                area_damage,
+            ));
+        }
+    }
+}
+
+fn pay_bounties(
+    mut entity_died_reader: EventReader<EntityDied>,
+    query: Query<&Bounty>,
+    mut mana: ResMut<Mana>,
+) {
+    for event in entity_died_reader.read() {
+        //  only kills pay, not units that expire or otherwise waste away
+        let (Some(_), DeathCause::Damage(_)) = (event.killer, event.cause) else {
+            continue;
+        };
+
+        if let Ok(bounty) = query.get(event.entity) {
+            mana.0 += bounty.mana;
+        }
+    }
+}
+
 fn despawn_dead_entities(mut commands: Commands, query: Query<(Entity, &Health)>) {
     for (entity, health) in query.iter() {
-        if health.value <= 0.0 {
+        if health.current <= 0.0 {
             commands.entity(entity).despawn_recursive();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collisions::CollisionsPlugin,
+        damage::{DamageEvent, DamageKind, DamagePlugin, Falloff},
+        group::Group,
+        schedule::test_app,
+    };
+
+    #[test]
+    fn every_death_is_reported_once() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin, DespawnPlugin))
+            .insert_resource(Mana(0.0));
+
+        let killer = app.world.spawn_empty().id();
+        let killed = app
+            .world
+            .spawn((
+                Health::new(5.0),
+                Bounty::new(10.0),
+                AreaDamage::new(1.0, DamageKind::Magic, 20.0, Falloff::Linear, Group::ENEMY),
+                TransformBundle::from_transform(Transform::from_xyz(30.0, 0.0, 0.0)),
+            ))
+            .id();
+        let drained = app
+            .world
+            .spawn((
+                Health::new(5.0),
+                Bounty::new(10.0),
+                TransformBundle::default(),
+            ))
+            .id();
+
+        let mut died_reader = app.world.resource::<Events<EntityDied>>().get_reader();
+        let mut area_damage_reader = app.world.resource::<Events<AreaDamageEvent>>().get_reader();
+        let mut deaths = vec![];
+        let mut detonations = vec![];
+
+        app.update();
+        //  killed twice over on the same frame
+        for _ in 0..2 {
+            app.world
+                .send_event(DamageEvent::new(killer, killed, 5.0, DamageKind::Pierce));
+        }
+        app.world.get_mut::<Health>(drained).unwrap().current = 0.0;
+
+        for _ in 0..3 {
+            app.update();
+            deaths.extend(
+                died_reader
+                    .read(app.world.resource::<Events<EntityDied>>())
+                    .map(|event| (event.entity, event.killer, event.cause, event.position)),
+            );
+            detonations.extend(
+                area_damage_reader
+                    .read(app.world.resource::<Events<AreaDamageEvent>>())
+                    .map(|event| (event.source, event.position)),
+            );
+        }
+
+        assert_eq!(deaths.len(), 2);
+        assert!(deaths.contains(&(
+            killed,
+            Some(killer),
+            DeathCause::Damage(DamageKind::Pierce),
+            Vec2::new(30.0, 0.0)
+        )));
+        assert!(deaths.contains(&(drained, None, DeathCause::Unknown, Vec2::ZERO)));
+        assert_eq!(detonations, vec![(killed, Vec2::new(30.0, 0.0))]);
+        //  only the kill pays out
+        assert_eq!(app.world.resource::<Mana>().0, 10.0);
+        assert!(app.world.get_entity(killed).is_none());
+        assert!(app.world.get_entity(drained).is_none());
+    }
+}
@@ -1,31 +1,33 @@
 use bevy::prelude::*;
 
 use crate::{
-    attack::AttackOccurance,
-    bubble::{Bubble, BubbleSpawner},
-    footman::Footman,
+    attack::{Attack, AttackOccurance},
+    collisions::{Collider, CollisionDamage},
+    damage::DamageDealt,
     group::Group,
+    health::Health,
     schedule::InGameSet,
-    tower::Tower,
+    spatial::SpatialQuery,
 };
 
-const ATTACK_RANGE: f32 = 40.0;
+const REVEAL_DURATION: f32 = 2.0;
+const MEMORY_DURATION: f32 = 5.0;
 
 pub struct DetectionPlugin;
 
 impl Plugin for DetectionPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                detect::<Bubble, Tower>,
-                detect::<Bubble, Footman>,
-                detect::<Footman, Bubble>,
-                detect::<Footman, BubbleSpawner>,
+                (detect, remember_lost_targets).chain(),
+                forget_last_seen,
+                reveal_cloaks,
             )
                 .in_set(InGameSet::EntityUpdates),
         )
-        .add_event::<DetectionEvent>();
+        .add_event::<TargetAcquired>()
+        .add_event::<TargetLost>();
     }
 }
 
@@ -42,23 +44,117 @@ impl Tracker {
 
 #[derive(Component, Debug)]
 pub struct Target;
-//  TODO: add cloaking, a 0..=1 value to reduce the tracker vision by
 
-#[derive(Event, Debug)]
-pub struct DetectionEvent {
-    pub tracker_entity: Entity,
-    pub target_entity: Entity,
+/// Hides a target from trackers, reducing their vision of it by `value` (0..=1).
+/// The target is revealed for a while after it attacks or gets hit.
+#[derive(Component, Debug)]
+pub struct Cloak {
+    pub value: f32,
+    pub reveal: Option<Timer>,
+}
+
+impl Cloak {
+    pub fn new(value: f32) -> Self {
+        Self {
+            value: value.clamp(0.0, 1.0),
+            reveal: None,
+        }
+    }
+
+    pub fn reveal(&mut self) {
+        self.reveal = Some(Timer::from_seconds(REVEAL_DURATION, TimerMode::Once));
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        self.reveal.is_some()
+    }
+
+    /// The share of a tracker's vision that still reaches the target.
+    pub fn visibility(&self) -> f32 {
+        if self.is_revealed() {
+            1.0
+        } else {
+            1.0 - self.value
+        }
+    }
+}
+
+/// Blocks the line of sight of trackers, hiding whatever is behind it.
+#[derive(Component, Debug)]
+pub struct Occluder;
+
+/// How a tracker picks one target among everything it can see.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetingPolicy {
+    Nearest,
+    #[allow(dead_code)]
+    LowestHealth,
+    /// Prefers targets dealing the most damage per second.
+    #[allow(dead_code)]
+    HighestThreat,
+    /// Prefers `Group::STRUCTURE` members, then the nearest.
+    StructuresFirst,
+    /// Keeps the current target while it stays in sight, otherwise the nearest.
+    Sticky,
+}
+
+/// The target a tracker picked on the last detection pass.
+#[derive(Component, Debug)]
+pub struct CurrentTarget {
+    pub entity: Entity,
+}
+
+impl CurrentTarget {
+    pub fn new(entity: Entity) -> Self {
+        Self { entity }
+    }
+}
+
+/// Where and when a tracker lost sight of a target, kept for a while afterwards.
+#[derive(Component, Debug)]
+pub struct LastSeen {
+    pub target: Entity,
+    pub position: Vec2,
+    /// Elapsed game time in seconds.
+    pub time: f32,
 }
 
-impl DetectionEvent {
-    pub fn new(tracker_entity: Entity, target_entity: Entity) -> Self {
+impl LastSeen {
+    pub fn new(target: Entity, position: Vec2, time: f32) -> Self {
         Self {
-            tracker_entity,
-            target_entity,
+            target,
+            position,
+            time,
         }
     }
 }
 
+/// Sent when a tracker picks a new target.
+#[derive(Event, Debug)]
+pub struct TargetAcquired {
+    pub tracker: Entity,
+    pub target: Entity,
+}
+
+impl TargetAcquired {
+    pub fn new(tracker: Entity, target: Entity) -> Self {
+        Self { tracker, target }
+    }
+}
+
+/// Sent when a tracker drops its target, because it left sight or another target was picked.
+#[derive(Event, Debug)]
+pub struct TargetLost {
+    pub tracker: Entity,
+    pub target: Entity,
+}
+
+impl TargetLost {
+    pub fn new(tracker: Entity, target: Entity) -> Self {
+        Self { tracker, target }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Component)]
 pub struct DetectionGroups {
     /// Groups memberships.
@@ -77,30 +173,55 @@ impl DetectionGroups {
     }
 }
 
-fn detect<T: Component, U: Component>(
+struct Candidate {
+    entity: Entity,
+    distance: f32,
+    health: f32,
+    threat: f32,
+    is_structure: bool,
+}
+
+type TrackerSettings = (
+    Option<&'static TargetingPolicy>,
+    Option<&'static CurrentTarget>,
+    Option<&'static Attack>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn detect(
     mut commands: Commands,
+    //  no need to track if there is attack
     trackers: Query<
-        (
-            Entity,
-            &DetectionGroups,
-            &Tracker,
-            &GlobalTransform,
-            Option<&AttackOccurance>,
-        ),
-        With<T>,
+        (Entity, &DetectionGroups, &Tracker, &GlobalTransform),
+        Without<AttackOccurance>,
     >,
-    targets: Query<(Entity, &DetectionGroups, &GlobalTransform), With<U>>,
-    mut tracking_event_writer: EventWriter<DetectionEvent>,
+    tracker_settings: Query<TrackerSettings>,
+    targets: Query<(&DetectionGroups, &GlobalTransform, Option<&Cloak>), With<Target>>,
+    target_stats: Query<(Option<&Health>, Option<&Attack>, Option<&CollisionDamage>)>,
+    target_colliders: Query<&Collider>,
+    occluders: Query<(), With<Occluder>>,
+    spatial_query: SpatialQuery,
+    mut target_acquired_writer: EventWriter<TargetAcquired>,
+    mut target_lost_writer: EventWriter<TargetLost>,
 ) {
-    for (tracker_entity, tracker_groups, tracker, tracker_transform, attack_option) in
-        trackers.iter()
-    {
-        //  no need to track if there is attack
-        if let Some(_) = attack_option {
+    for (tracker_entity, tracker_groups, tracker, tracker_transform) in trackers.iter() {
+        let Ok((policy, current_target, tracker_attack)) = tracker_settings.get(tracker_entity)
+        else {
             continue;
-        }
+        };
+
+        let nearby_entities = spatial_query.overlap_circle(
+            tracker_transform.translation().truncate(),
+            tracker.vision,
+            Group::ALL,
+        );
+        let mut candidates: Vec<Candidate> = vec![];
+
+        for target_entity in nearby_entities {
+            let Ok((target_groups, target_transform, cloak)) = targets.get(target_entity) else {
+                continue;
+            };
 
-        for (target_entity, target_groups, target_transform) in targets.iter() {
             if tracker_entity == target_entity {
                 continue;
             }
@@ -118,17 +239,552 @@ fn detect<T: Component, U: Component>(
                 continue;
             }
 
-            match distance {
-                i if i < ATTACK_RANGE => {
-                    commands
-                        .entity(tracker_entity)
-                        .insert(AttackOccurance::new(tracker_entity, target_entity));
-                }
-                i if i < tracker.vision => {
-                    tracking_event_writer.send(DetectionEvent::new(tracker_entity, target_entity));
-                }
-                _ => continue,
+            let vision = tracker.vision * cloak.map_or(1.0, Cloak::visibility);
+
+            //  cloaked targets cannot be attacked unless they can be seen
+            if distance >= vision {
+                continue;
+            }
+
+            //  anything occluding in between blocks the line of sight
+            let tracker_position = tracker_transform.translation().truncate();
+            let is_occluded = spatial_query
+                .cast_ray(
+                    tracker_position,
+                    target_transform.translation().truncate() - tracker_position,
+                    distance,
+                    Group::ALL,
+                )
+                .iter()
+                .any(|hit| {
+                    hit.entity != tracker_entity
+                        && hit.entity != target_entity
+                        && occluders.contains(hit.entity)
+                });
+
+            if is_occluded {
+                continue;
+            }
+
+            let Ok((health, attack, collision_damage)) = target_stats.get(target_entity) else {
+                continue;
+            };
+
+            candidates.push(Candidate {
+                entity: target_entity,
+                distance,
+                health: health.map_or(f32::MAX, |health| health.current),
+                //  a zero-duration rate would divide by zero
+                threat: attack.map_or(0.0, |attack| {
+                    attack.amount / attack.rate.duration().as_secs_f32().max(f32::EPSILON)
+                }) + collision_damage.map_or(0.0, |damage| damage.amount),
+                is_structure: target_groups.memberships.contains(Group::STRUCTURE),
+            });
+        }
+
+        let policy = policy.copied().unwrap_or(TargetingPolicy::Nearest);
+        let current_entity = current_target.map(|current_target| current_target.entity);
+
+        let target = select_target(policy, &candidates, current_entity);
+        let target_entity = target.map(|target| target.entity);
+
+        if target_entity != current_entity {
+            if let Some(current_entity) = current_entity {
+                target_lost_writer.send(TargetLost::new(tracker_entity, current_entity));
+            }
+
+            if let Some(target_entity) = target_entity {
+                target_acquired_writer.send(TargetAcquired::new(tracker_entity, target_entity));
             }
         }
+
+        let Some(target) = target else {
+            commands.entity(tracker_entity).remove::<CurrentTarget>();
+            continue;
+        };
+
+        commands
+            .entity(tracker_entity)
+            .insert(CurrentTarget::new(target.entity));
+
+        //  only trackers that can attack stop to do so
+        let Some(tracker_attack) = tracker_attack else {
+            continue;
+        };
+
+        let Ok((_, target_transform, _)) = targets.get(target.entity) else {
+            continue;
+        };
+
+        if tracker_attack.reaches(
+            tracker_transform.translation().truncate(),
+            target_transform,
+            target_colliders.get(target.entity).ok(),
+        ) {
+            commands
+                .entity(tracker_entity)
+                .insert(AttackOccurance::new(tracker_entity, target.entity));
+        }
+    }
+}
+
+fn select_target(
+    policy: TargetingPolicy,
+    candidates: &[Candidate],
+    current_entity: Option<Entity>,
+) -> Option<&Candidate> {
+    let nearest = |a: &&Candidate, b: &&Candidate| a.distance.total_cmp(&b.distance);
+
+    match policy {
+        TargetingPolicy::Nearest => candidates.iter().min_by(nearest),
+        TargetingPolicy::LowestHealth => candidates
+            .iter()
+            .min_by(|a, b| a.health.total_cmp(&b.health).then(nearest(a, b))),
+        TargetingPolicy::HighestThreat => candidates
+            .iter()
+            .min_by(|a, b| b.threat.total_cmp(&a.threat).then(nearest(a, b))),
+        TargetingPolicy::StructuresFirst => candidates
+            .iter()
+            .min_by(|a, b| b.is_structure.cmp(&a.is_structure).then(nearest(a, b))),
+        TargetingPolicy::Sticky => candidates
+            .iter()
+            .find(|candidate| Some(candidate.entity) == current_entity)
+            .or_else(|| candidates.iter().min_by(nearest)),
+    }
+}
+
+fn remember_lost_targets(
+    mut commands: Commands,
+    mut target_lost_reader: EventReader<TargetLost>,
+    mut target_acquired_reader: EventReader<TargetAcquired>,
+    last_seen_query: Query<&LastSeen>,
+    target_query: Query<&GlobalTransform, With<Target>>,
+    time: Res<Time>,
+) {
+    //  found again, so no need to look for it (before the losses, so those are kept)
+    for event in target_acquired_reader.read() {
+        if let Ok(last_seen) = last_seen_query.get(event.tracker) {
+            if last_seen.target == event.target {
+                commands.entity(event.tracker).remove::<LastSeen>();
+            }
+        }
+    }
+
+    for event in target_lost_reader.read() {
+        //  nothing left to look for once it is gone
+        let Ok(target_transform) = target_query.get(event.target) else {
+            continue;
+        };
+
+        commands.entity(event.tracker).insert(LastSeen::new(
+            event.target,
+            target_transform.translation().truncate(),
+            time.elapsed_seconds(),
+        ));
+    }
+}
+
+fn forget_last_seen(mut commands: Commands, query: Query<(Entity, &LastSeen)>, time: Res<Time>) {
+    for (entity, last_seen) in query.iter() {
+        if time.elapsed_seconds() - last_seen.time > MEMORY_DURATION {
+            commands.entity(entity).remove::<LastSeen>();
+        }
+    }
+}
+
+fn reveal_cloaks(
+    mut cloaks: Query<&mut Cloak>,
+    attacker_query: Query<(Entity, &Attack, &AttackOccurance)>,
+    mut damage_dealt_reader: EventReader<DamageDealt>,
+    time: Res<Time>,
+) {
+    for mut cloak in cloaks.iter_mut() {
+        let Some(reveal) = cloak.reveal.as_mut() else {
+            continue;
+        };
+
+        reveal.tick(time.delta());
+
+        if reveal.finished() {
+            cloak.reveal = None;
+        }
+    }
+
+    let mut revealed_entities: Vec<Entity> = vec![];
+
+    //  attacking
+    for (entity, attack, occurance) in attacker_query.iter() {
+        if attack.rate.just_finished() {
+            revealed_entities.push(entity);
+            revealed_entities.push(occurance.target);
+        }
+    }
+
+    //  being hit, or dealing damage by other means like collisions or area damage
+    for DamageDealt(event) in damage_dealt_reader.read() {
+        revealed_entities.push(event.source);
+        revealed_entities.push(event.target);
+    }
+
+    for entity in revealed_entities {
+        if let Ok(mut cloak) = cloaks.get_mut(entity) {
+            cloak.reveal();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::ManualEventReader, system::RunSystemOnce};
+
+    use super::*;
+    use crate::{
+        bubble::BubbleSpawner,
+        collisions::{Collider, CollisionGroups, CollisionsPlugin},
+        damage::{DamageEvent, DamageKind, DamagePlugin, Shield},
+        footman::{spawn_footman, Footman},
+        harvester::{Harvester, HarvesterPlugin},
+        schedule::test_app,
+        shape::ColliderShape,
+        Mana,
+    };
+
+    #[test]
+    fn footman_ignores_harvesters() {
+        let mut app = test_app();
+        app.add_plugins((AssetPlugin::default(), CollisionsPlugin, DetectionPlugin))
+            .add_plugins(HarvesterPlugin)
+            .init_asset::<Image>()
+            .insert_resource(Mana(100.0))
+            .init_resource::<ButtonInput<KeyCode>>();
+
+        app.world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+        assert_eq!(
+            app.world
+                .query_filtered::<(), With<Harvester>>()
+                .iter(&app.world)
+                .count(),
+            1
+        );
+
+        //  close enough to attack the harvester, were it a target
+        app.world
+            .run_system_once(|mut commands: Commands, asset_server: Res<AssetServer>| {
+                spawn_footman(&mut commands, &asset_server, Vec3::new(30.0, 0.0, 0.0));
+            });
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world
+                .query_filtered::<(), (
+                    With<Footman>,
+                    Or<(With<CurrentTarget>, With<AttackOccurance>)>
+                )>()
+                .iter(&app.world)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn cloaked_spawner_is_seen_only_after_being_hit() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin, DetectionPlugin));
+
+        let footman = app
+            .world
+            .spawn((
+                Footman,
+                Tracker::new(100.0),
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+                TransformBundle::default(),
+            ))
+            .id();
+        //  within vision, but not within the half of it the cloak leaves
+        let spawner = app
+            .world
+            .spawn((
+                BubbleSpawner {
+                    spawn_rate: Timer::from_seconds(1.0, TimerMode::Repeating),
+                },
+                Target,
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Cloak::new(0.5),
+                Health::new(80.0),
+                Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(70.0, 0.0, 0.0)),
+            ))
+            .id();
+
+        let detected = |app: &App| app.world.get::<CurrentTarget>(footman).is_some();
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(!detected(&app));
+
+        app.world
+            .send_event(DamageEvent::new(footman, spawner, 1.0, DamageKind::Pierce));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world.get::<Cloak>(spawner).unwrap().is_revealed());
+        assert!(detected(&app));
+
+        //  hidden again once the reveal wears off
+        for _ in 0..(REVEAL_DURATION * 60.0) as usize {
+            app.update();
+        }
+        assert!(!app.world.get::<Cloak>(spawner).unwrap().is_revealed());
+        assert!(!detected(&app));
+    }
+
+    #[test]
+    fn dealing_damage_reveals_the_source() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin, DetectionPlugin));
+
+        let source = app.world.spawn(Cloak::new(0.5)).id();
+        let shielded = app
+            .world
+            .spawn((
+                Health::new(10.0),
+                Shield::new(5.0, 0.0, 1.0),
+                TransformBundle::default(),
+            ))
+            .id();
+        let target = app
+            .world
+            .spawn((Health::new(10.0), TransformBundle::default()))
+            .id();
+
+        app.update();
+        assert!(!app.world.get::<Cloak>(source).unwrap().is_revealed());
+
+        //  a hit the shield soaks up deals no damage
+        app.world
+            .send_event(DamageEvent::new(source, shielded, 1.0, DamageKind::Pierce));
+        app.update();
+        app.update();
+        assert!(!app.world.get::<Cloak>(source).unwrap().is_revealed());
+
+        app.world
+            .send_event(DamageEvent::new(source, target, 1.0, DamageKind::Pierce));
+        app.update();
+        app.update();
+        assert!(app.world.get::<Cloak>(source).unwrap().is_revealed());
+    }
+
+    #[test]
+    fn occluders_block_line_of_sight() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DetectionPlugin));
+
+        let tracker = app
+            .world
+            .spawn((
+                Tracker::new(200.0),
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+                TransformBundle::default(),
+            ))
+            .id();
+        let target = app
+            .world
+            .spawn((
+                Target,
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Collider::new(ColliderShape::Circle { radius: 8.0 }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(150.0, 0.0, 0.0)),
+            ))
+            .id();
+        let wall = app
+            .world
+            .spawn((
+                Occluder,
+                Collider::new(ColliderShape::Aabb {
+                    half_extents: Vec2::new(10.0, 40.0),
+                }),
+                CollisionGroups::new(Group::ENEMY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(75.0, 0.0, 0.0)),
+            ))
+            .id();
+
+        app.update();
+        app.update();
+        assert!(app.world.get::<CurrentTarget>(tracker).is_none());
+
+        app.world.despawn(wall);
+        app.update();
+        assert_eq!(
+            app.world.get::<CurrentTarget>(tracker).unwrap().entity,
+            target
+        );
+    }
+
+    #[test]
+    fn lost_targets_are_remembered_for_a_while() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DetectionPlugin));
+
+        let tracker = app
+            .world
+            .spawn((
+                Tracker::new(100.0),
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+                TransformBundle::default(),
+            ))
+            .id();
+        let target = app
+            .world
+            .spawn((
+                Target,
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Collider::new(ColliderShape::Circle { radius: 8.0 }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(60.0, 0.0, 0.0)),
+            ))
+            .id();
+        //  the broadphase only catches up on the frame after
+        let move_target = |app: &mut App, x: f32| {
+            app.world
+                .get_mut::<Transform>(target)
+                .unwrap()
+                .translation
+                .x = x;
+            app.update();
+            app.update();
+        };
+
+        let mut acquired = ManualEventReader::<TargetAcquired>::default();
+        let mut lost = ManualEventReader::<TargetLost>::default();
+        let mut read = |app: &App| {
+            (
+                acquired
+                    .read(app.world.resource::<Events<TargetAcquired>>())
+                    .count(),
+                lost.read(app.world.resource::<Events<TargetLost>>())
+                    .count(),
+            )
+        };
+
+        app.update();
+        app.update();
+        app.update();
+        assert_eq!(read(&app), (1, 0));
+
+        move_target(&mut app, 150.0);
+        assert_eq!(read(&app), (0, 1));
+        assert!(app.world.get::<CurrentTarget>(tracker).is_none());
+        let last_seen = app.world.get::<LastSeen>(tracker).unwrap();
+        assert_eq!(last_seen.target, target);
+        assert_eq!(last_seen.position, Vec2::new(150.0, 0.0));
+
+        //  found again
+        move_target(&mut app, 60.0);
+        assert_eq!(read(&app), (1, 0));
+        assert!(app.world.get::<LastSeen>(tracker).is_none());
+
+        move_target(&mut app, 150.0);
+        assert!(app.world.get::<LastSeen>(tracker).is_some());
+        for _ in 0..(MEMORY_DURATION * 60.0) as usize + 1 {
+            app.update();
+        }
+        assert!(app.world.get::<LastSeen>(tracker).is_none());
+    }
+
+    #[test]
+    fn each_policy_picks_its_own_target() {
+        let candidate = |index, distance, health, threat, is_structure| Candidate {
+            entity: Entity::from_raw(index),
+            distance,
+            health,
+            threat,
+            is_structure,
+        };
+        let candidates = [
+            candidate(0, 10.0, 100.0, 5.0, false),
+            candidate(1, 50.0, 20.0, 0.0, false),
+            candidate(2, 60.0, 100.0, 30.0, false),
+            candidate(3, 80.0, 300.0, 0.0, true),
+        ];
+        let pick = |policy, current| {
+            select_target(policy, &candidates, current).map(|candidate| candidate.entity.index())
+        };
+
+        assert_eq!(pick(TargetingPolicy::Nearest, None), Some(0));
+        assert_eq!(pick(TargetingPolicy::LowestHealth, None), Some(1));
+        assert_eq!(pick(TargetingPolicy::HighestThreat, None), Some(2));
+        assert_eq!(pick(TargetingPolicy::StructuresFirst, None), Some(3));
+        assert_eq!(
+            pick(TargetingPolicy::Sticky, Some(Entity::from_raw(2))),
+            Some(2)
+        );
+        //  the old target is out of sight
+        assert_eq!(
+            pick(TargetingPolicy::Sticky, Some(Entity::from_raw(9))),
+            Some(0)
+        );
+        assert_eq!(
+            select_target(TargetingPolicy::Nearest, &[], None).map(|c| c.entity),
+            None
+        );
+    }
+
+    #[test]
+    fn zero_duration_attacks_carry_no_threat() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DetectionPlugin));
+
+        let tracker = app
+            .world
+            .spawn((
+                Tracker::new(100.0),
+                TargetingPolicy::HighestThreat,
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+                TransformBundle::default(),
+            ))
+            .id();
+        let target = |amount: f32, rate: f32, x: f32| {
+            (
+                Target,
+                Attack::new(
+                    amount,
+                    DamageKind::Pierce,
+                    40.0,
+                    Timer::from_seconds(rate, TimerMode::Repeating),
+                ),
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Collider::new(ColliderShape::Circle { radius: 8.0 }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+            )
+        };
+        let attacker = app.world.spawn(target(10.0, 1.0, 80.0)).id();
+        //  0 / 0 is NaN, which sorts above or below every real threat
+        let instant = app.world.spawn(target(0.0, 0.0, 60.0)).id();
+        app.world.spawn(target(0.0, 1.0, 90.0));
+
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world.get::<CurrentTarget>(tracker).unwrap().entity,
+            attacker
+        );
+
+        //  harmless either way, so the nearer one wins
+        app.world.despawn(attacker);
+        app.update();
+        assert_eq!(
+            app.world.get::<CurrentTarget>(tracker).unwrap().entity,
+            instant
+        );
     }
 }
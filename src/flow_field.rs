@@ -0,0 +1,170 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+
+use crate::{
+    pathfinding::{update_nav_grid, NavGrid},
+    schedule::InGameSet,
+};
+
+/// How many cells the field reaches beyond its goals, as the grid has no edges.
+const FLOW_FIELD_MARGIN: i32 = 48;
+
+pub struct FlowFieldPlugin;
+
+impl Plugin for FlowFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_flow_fields
+                .after(update_nav_grid)
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .insert_resource(FlowFields::default());
+    }
+}
+
+/// Marks an entity as one of the destinations of a shared flow field.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlowGoal {
+    Harvesters,
+}
+
+/// Direction towards the nearest goal for every navigation cell around them.
+#[derive(Debug, Default)]
+pub struct FlowField {
+    cell_size: f32,
+    goals: Vec<Vec2>,
+    directions: HashMap<IVec2, Vec2>,
+}
+
+impl FlowField {
+    fn new(nav_grid: &NavGrid, goals: Vec<Vec2>) -> Self {
+        let goal_cells: Vec<IVec2> = goals.iter().map(|&goal| nav_grid.cell(goal)).collect();
+        let min = goal_cells
+            .iter()
+            .copied()
+            .reduce(IVec2::min)
+            .unwrap_or_default()
+            - IVec2::splat(FLOW_FIELD_MARGIN);
+        let max = goal_cells
+            .iter()
+            .copied()
+            .reduce(IVec2::max)
+            .unwrap_or_default()
+            + IVec2::splat(FLOW_FIELD_MARGIN);
+
+        //  dijkstra outwards from every goal at once
+        let mut open: BinaryHeap<Reverse<(u32, i32, i32)>> = BinaryHeap::new();
+        let mut costs: HashMap<IVec2, u32> = HashMap::new();
+
+        for &cell in goal_cells.iter() {
+            costs.insert(cell, 0);
+            open.push(Reverse((0, cell.x, cell.y)));
+        }
+
+        while let Some(Reverse((cost, x, y))) = open.pop() {
+            let cell = IVec2::new(x, y);
+
+            if costs.get(&cell).is_some_and(|&known| known < cost) {
+                continue;
+            }
+
+            //  goals inside obstacles spread through them until they reach open ground
+            for (next, step_cost) in nav_grid.neighbors(cell, !nav_grid.is_walkable(cell)) {
+                if next.cmplt(min).any() || next.cmpgt(max).any() {
+                    continue;
+                }
+
+                let next_cost = cost + step_cost;
+
+                if costs.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+
+                costs.insert(next, next_cost);
+                open.push(Reverse((next_cost, next.x, next.y)));
+            }
+        }
+
+        //  every cell points at its cheapest neighbor
+        let directions = costs
+            .iter()
+            .filter(|&(_, &cost)| cost > 0)
+            .filter_map(|(&cell, &cost)| {
+                let (next, _) = nav_grid
+                    .neighbors(cell, !nav_grid.is_walkable(cell))
+                    .into_iter()
+                    .filter_map(|(next, _)| Some((next, *costs.get(&next)?)))
+                    .filter(|&(_, next_cost)| next_cost < cost)
+                    .min_by_key(|&(_, next_cost)| next_cost)?;
+
+                Some((cell, (next - cell).as_vec2().normalize()))
+            })
+            .collect();
+
+        Self {
+            cell_size: nav_grid.cell_size,
+            goals,
+            directions,
+        }
+    }
+
+    /// Unit direction to follow from `position`, heading straight for the nearest goal
+    /// outside the field or once in a goal cell.
+    pub fn direction(&self, position: Vec2) -> Option<Vec2> {
+        let cell = (position / self.cell_size).floor().as_ivec2();
+
+        if let Some(&direction) = self.directions.get(&cell) {
+            return Some(direction);
+        }
+
+        let nearest_goal = self
+            .goals
+            .iter()
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))?;
+
+        (*nearest_goal - position).try_normalize()
+    }
+}
+
+/// One flow field per `FlowGoal`, shared by every unit heading there.
+#[derive(Resource, Debug, Default)]
+pub struct FlowFields {
+    fields: HashMap<FlowGoal, FlowField>,
+}
+
+impl FlowFields {
+    pub fn get(&self, goal: FlowGoal) -> Option<&FlowField> {
+        self.fields.get(&goal)
+    }
+}
+
+fn update_flow_fields(
+    mut flow_fields: ResMut<FlowFields>,
+    nav_grid: Res<NavGrid>,
+    goal_query: Query<(&FlowGoal, &GlobalTransform)>,
+    changed_goal_query: Query<(), (With<FlowGoal>, Changed<GlobalTransform>)>,
+    mut removed_goals: RemovedComponents<FlowGoal>,
+) {
+    let is_removed = removed_goals.read().count() > 0;
+
+    //  buildings placed or destroyed change the nav grid
+    if !nav_grid.is_changed() && !is_removed && changed_goal_query.is_empty() {
+        return;
+    }
+
+    let mut goals: HashMap<FlowGoal, Vec<Vec2>> = HashMap::new();
+
+    for (&goal, transform) in goal_query.iter() {
+        goals
+            .entry(goal)
+            .or_insert_with(Vec::new)
+            .push(transform.translation().truncate());
+    }
+
+    flow_fields.fields = goals
+        .into_iter()
+        .map(|(goal, positions)| (goal, FlowField::new(&nav_grid, positions)))
+        .collect();
+}
@@ -4,22 +4,43 @@ use bevy::{prelude::*, sprite::Anchor};
 
 use crate::{
     attack::{Attack, AttackOccurance},
-    bubble::BubbleSpawner,
-    collisions::{Collider, CollisionDamage, CollisionGroups},
-    detection::{DetectionEvent, DetectionGroups, Target, Tracker},
+    collisions::{Collider, CollisionDamage, CollisionDamageMode, CollisionGroups, Mass},
+    damage::DamageKind,
+    despawn::Bounty,
+    detection::{CurrentTarget, DetectionGroups, LastSeen, Target, TargetingPolicy, Tracker},
+    flow_field::{FlowFields, FlowGoal},
     group::Group,
-    health::Health,
-    movement::{Acceleration, KinematicBundle, Velocity},
-    schedule::InGameSet,
+    health::{Health, HealthBar},
+    movement::{Acceleration, Friction, KinematicBundle, Velocity},
+    pathfinding::{plan_paths, PathFollower},
+    schedule::{InGameSet, TransformInterpolation},
+    shape::ColliderShape,
+    status::{OnHitEffects, StatusEffect, StatusKind},
+    steering::{steer, Steering, SteeringWeights},
 };
 
 const Z_LAYER: f32 = 0.0;
 const HEALTH: f32 = 10.0;
+const BOUNTY: f32 = 5.0;
 const COLLIDER_RADIUS: f32 = 16.0;
 const DETECTION_RADIUS: f32 = 600.0;
 const DAMAGE: f32 = 5.0;
+const ATTACK_RANGE: f32 = 40.0;
 const ATTACK_RATE: f32 = 1.2;
 const VELOCITY_RATE: f32 = 80.;
+const MASS: f32 = 4.0;
+const FRICTION: f32 = 240.0;
+const STEERING_FORCE: f32 = 480.0;
+const SEPARATION_RADIUS: f32 = 40.0;
+const SLOWING_RADIUS: f32 = 48.0;
+const LOOK_AHEAD: f32 = 64.0;
+const SEPARATION_WEIGHT: f32 = 0.8;
+const ARRIVAL_WEIGHT: f32 = 1.0;
+const AVOIDANCE_WEIGHT: f32 = 1.0;
+const HEALTH_BAR_WIDTH: f32 = 32.0;
+const HEALTH_BAR_OFFSET: f32 = 24.0;
+const POISON_DAMAGE: f32 = 1.0;
+const POISON_DURATION: f32 = 3.0;
 
 const ATTACK_END_TRANSLATION: Vec3 = Vec3::new(-16., -16., Z_LAYER);
 const ATTACK_START_TRANSLATION: Vec3 = Vec3::new(-16., 0., Z_LAYER);
@@ -31,8 +52,12 @@ pub struct FootmanPlugin;
 impl Plugin for FootmanPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            (tracking::<BubbleSpawner>, spear_attack_animation).in_set(InGameSet::EntityUpdates),
+            FixedUpdate,
+            (
+                tracking.before(plan_paths).before(steer),
+                spear_attack_animation,
+            )
+                .in_set(InGameSet::EntityUpdates),
         );
     }
 }
@@ -63,21 +88,39 @@ pub fn spawn_footman(commands: &mut Commands, asset_server: &Res<AssetServer>, l
                 transform: footman_transform,
                 ..default()
             },
-            KinematicBundle {
-                velocity: Velocity::new(Vec3::ZERO),
-                acceleration: Acceleration::new(Vec3::ZERO),
-            },
-            Collider::new(COLLIDER_RADIUS),
-            CollisionGroups::new(Group::ENEMY, Group::ALLY | Group::PLAYER),
-            CollisionDamage::new(DAMAGE),
-            DetectionGroups::new(Group::ENEMY, Group::ALLY | Group::PLAYER),
-            Tracker::new(DETECTION_RADIUS),
-            Target,
+            footman_movement(),
+            Collider::new(ColliderShape::Circle {
+                radius: COLLIDER_RADIUS,
+            }),
+            //  footmen only jostle each other through NPC, so they never snag on the tower
+            CollisionGroups::new(
+                Group::ENEMY | Group::NPC,
+                Group::ALLY | Group::PLAYER | Group::NPC,
+            ),
+            Mass::new(MASS),
+            CollisionDamage::new(DAMAGE, DamageKind::Pierce, CollisionDamageMode::OnEnter),
+            (
+                DetectionGroups::new(Group::ENEMY, Group::ALLY | Group::PLAYER),
+                Tracker::new(DETECTION_RADIUS),
+                TargetingPolicy::StructuresFirst,
+                Target,
+            ),
             Attack::new(
                 DAMAGE,
+                DamageKind::Pierce,
+                ATTACK_RANGE,
                 Timer::from_seconds(ATTACK_RATE, TimerMode::Repeating),
             ),
-            Health::new(HEALTH),
+            OnHitEffects::new(vec![StatusEffect::new(
+                StatusKind::Poison,
+                POISON_DAMAGE,
+                POISON_DURATION,
+            )]),
+            (
+                Health::new(HEALTH),
+                HealthBar::new(HEALTH_BAR_WIDTH, HEALTH_BAR_OFFSET),
+            ),
+            Bounty::new(BOUNTY),
             Footman,
             Name::new("Footman"),
         ))
@@ -103,30 +146,103 @@ pub fn spawn_footman(commands: &mut Commands, asset_server: &Res<AssetServer>, l
     //  must spawn a spear child; give spear Sprite.Anchor.BottomCenter
 }
 
-fn tracking<T: Component>(
-    mut detection_event_reader: EventReader<DetectionEvent>,
-    mut tracker_query: Query<(&GlobalTransform, &mut Velocity), With<Footman>>,
-    target_query: Query<&GlobalTransform, With<T>>,
+fn footman_movement() -> impl Bundle {
+    (
+        KinematicBundle {
+            velocity: Velocity::new(Vec3::ZERO),
+            acceleration: Acceleration::new(Vec3::ZERO),
+            interpolation: TransformInterpolation::default(),
+        },
+        Friction::new(FRICTION),
+        PathFollower::new(VELOCITY_RATE),
+        Steering::new(
+            SteeringWeights::new(SEPARATION_WEIGHT, ARRIVAL_WEIGHT, AVOIDANCE_WEIGHT),
+            VELOCITY_RATE,
+            STEERING_FORCE,
+            SEPARATION_RADIUS,
+            SLOWING_RADIUS,
+            LOOK_AHEAD,
+        ),
+    )
+}
+
+//  attacking units stay put until their target dies
+type TrackingFilter = (With<Footman>, Without<AttackOccurance>);
+
+fn tracking(
+    mut commands: Commands,
+    mut tracker_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut PathFollower,
+            &mut Steering,
+            &Attack,
+        ),
+        TrackingFilter,
+    >,
+    current_target_query: Query<&CurrentTarget>,
+    last_seen_query: Query<&LastSeen>,
+    target_query: Query<(&GlobalTransform, Option<&Collider>), With<Target>>,
+    flow_fields: Res<FlowFields>,
 ) {
-    for &DetectionEvent {
-        tracker_entity,
-        target_entity,
-    } in detection_event_reader.read()
+    for (entity, tracker_transform, mut path_follower, mut steering, attack) in
+        tracker_query.iter_mut()
     {
-        let Ok((tracker_transform, mut velocity)) = tracker_query.get_mut(tracker_entity) else {
-            continue;
-        };
+        let current_target = current_target_query.get(entity).ok();
+        let last_seen = last_seen_query.get(entity).ok();
+        let tracker_position = tracker_transform.translation().truncate();
+
+        //  chase the target, or investigate where it was last seen,
+        //  along with how close to it the unit stops
+        let (destination, stopping_distance) = match (current_target, last_seen) {
+            (Some(current_target), _) => {
+                let Ok((target_transform, target_collider)) =
+                    target_query.get(current_target.entity)
+                else {
+                    continue;
+                };
+
+                //  stop at attack range and let detection start the attack
+                if attack.reaches(tracker_position, target_transform, target_collider) {
+                    path_follower.goal = None;
+                    steering.desired_velocity = Vec2::ZERO;
+                    continue;
+                }
 
-        let Ok(target_transform) = target_query.get(target_entity) else {
-            continue;
+                (
+                    target_transform.translation().truncate(),
+                    attack.range
+                        + target_collider.map_or(0.0, |collider| collider.shape.bounding_radius()),
+                )
+            }
+            (None, Some(last_seen)) => {
+                if tracker_position.distance(last_seen.position) <= COLLIDER_RADIUS {
+                    commands.entity(entity).remove::<LastSeen>();
+                    path_follower.goal = None;
+                    steering.desired_velocity = Vec2::ZERO;
+                    continue;
+                }
+
+                (last_seen.position, COLLIDER_RADIUS)
+            }
+            //  idle units march with the wave towards the harvesters
+            (None, None) => {
+                path_follower.goal = None;
+                steering.destination = None;
+                steering.desired_velocity = flow_fields
+                    .get(FlowGoal::Harvesters)
+                    .and_then(|flow_field| flow_field.direction(tracker_position))
+                    .map_or(Vec2::ZERO, |direction| direction * VELOCITY_RATE);
+                continue;
+            }
         };
 
-        let ttt: Vec3 = target_transform.translation();
-        let planar_transform = Transform::from_xyz(ttt.x, ttt.y, tracker_transform.translation().z);
-        let direction =
-            (planar_transform.translation - tracker_transform.translation()).normalize();
+        steering.destination = Some(destination);
+        steering.stopping_distance = stopping_distance;
 
-        velocity.value = direction * VELOCITY_RATE;
+        //  the path follower picks the desired velocity around obstacles
+        path_follower.goal = Some(destination);
     }
 }
 
@@ -158,3 +274,47 @@ fn spear_attack_animation(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collisions::CollisionsPlugin, movement::MovementPlugin, schedule::test_app,
+        steering::SteeringPlugin,
+    };
+
+    #[test]
+    fn knocked_back_footman_coasts_to_a_stop() {
+        let mut app = test_app();
+        app.add_plugins((MovementPlugin, SteeringPlugin, CollisionsPlugin));
+
+        let footman = app
+            .world
+            .spawn((
+                TransformBundle::default(),
+                footman_movement(),
+                Collider::new(ColliderShape::Circle {
+                    radius: COLLIDER_RADIUS,
+                }),
+                CollisionGroups::new(Group::ENEMY, Group::ALLY | Group::PLAYER | Group::ENEMY),
+            ))
+            .id();
+
+        //  as if a bubble just bounced off it
+        app.world.get_mut::<Velocity>(footman).unwrap().value = Vec3::new(300.0, 0.0, 0.0);
+
+        let mut speeds = vec![];
+        for _ in 0..60 {
+            app.update();
+            speeds.push(app.world.get::<Velocity>(footman).unwrap().value.x);
+        }
+
+        //  slowed down a little at a time, rather than reset to its walking pace
+        assert!(speeds[0] > VELOCITY_RATE);
+        assert!(speeds.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(speeds[59].abs() < 1e-3);
+
+        let position = app.world.get::<Transform>(footman).unwrap().translation;
+        assert!(position.x > 50.0);
+    }
+}
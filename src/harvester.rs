@@ -1,12 +1,14 @@
 use bevy::prelude::*;
 
 use crate::{
-    collisions::{Collider, CollisionGroups},
-    detection::{DetectionGroups, Target},
+    collisions::{Collider, CollisionEnded, CollisionGroups, CollisionStarted, Sensor, Static},
+    detection::DetectionGroups,
+    flow_field::FlowGoal,
     group::Group,
-    health::Health,
+    health::{Health, HealthBar},
     player::Player,
-    schedule::InGameSet,
+    schedule::{InGameSet, InputSet},
+    shape::ColliderShape,
     Mana,
 };
 
@@ -15,17 +17,20 @@ const HEALTH: f32 = 200.0;
 const COLLIDER_RADIUS: f32 = 48.0;
 const BASE_GENERATION_RATE: f32 = 2.0;
 const MAX_MANA: f32 = 100.0;
-const DRAIN_RADIUS: f32 = 140.0;
+//  the player's collider makes up the rest of the old 140 reach
+const DRAIN_RADIUS: f32 = 124.0;
 const DRAIN_RATE: f32 = 40.0;
+const HEALTH_BAR_WIDTH: f32 = 80.0;
+const HEALTH_BAR_OFFSET: f32 = 60.0;
 
 pub struct HarvesterPlugin;
 
 impl Plugin for HarvesterPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, spawn_harvester.in_set(InGameSet::UserInput))
+        app.add_systems(Update, spawn_harvester.in_set(InputSet))
             .add_systems(
-                Update,
-                (generate_mana, drain_mana)
+                FixedUpdate,
+                (generate_mana, track_drain_areas, drain_mana)
                     .chain()
                     .in_set(InGameSet::EntityUpdates),
             );
@@ -47,6 +52,12 @@ impl Harvester {
     }
 }
 
+/// Sensor around a harvester; the player drains its mana while overlapping it.
+#[derive(Component, Debug, Default)]
+pub struct DrainArea {
+    pub is_occupied: bool,
+}
+
 fn spawn_harvester(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -62,19 +73,36 @@ fn spawn_harvester(
 
         let texture: Handle<Image> = asset_server.load("harvester.png");
 
-        commands.spawn((
-            SpriteBundle {
-                texture,
-                ..default()
-            },
-            Collider::new(COLLIDER_RADIUS),
-            CollisionGroups::new(Group::ALLY, Group::NONE),
-            DetectionGroups::new(Group::ALLY, Group::NONE),
-            Target,
-            Health::new(HEALTH),
-            Harvester::new(0.0, BASE_GENERATION_RATE),
-            Name::new("Harvester"),
-        ));
+        commands
+            .spawn((
+                SpriteBundle {
+                    texture,
+                    ..default()
+                },
+                Collider::new(ColliderShape::Circle {
+                    radius: COLLIDER_RADIUS,
+                }),
+                CollisionGroups::new(Group::ALLY, Group::NONE),
+                Static,
+                DetectionGroups::new(Group::ALLY, Group::NONE),
+                Health::new(HEALTH),
+                HealthBar::new(HEALTH_BAR_WIDTH, HEALTH_BAR_OFFSET),
+                Harvester::new(0.0, BASE_GENERATION_RATE),
+                FlowGoal::Harvesters,
+                Name::new("Harvester"),
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    SpatialBundle::default(),
+                    Collider::new(ColliderShape::Circle {
+                        radius: DRAIN_RADIUS,
+                    }),
+                    CollisionGroups::new(Group::NONE, Group::PLAYER),
+                    Sensor,
+                    DrainArea::default(),
+                    Name::new("DrainArea"),
+                ));
+            });
     }
 }
 
@@ -90,31 +118,122 @@ fn generate_mana(time: Res<Time>, mut harvesters: Query<&mut Harvester>) {
     }
 }
 
+fn track_drain_areas(
+    mut collision_started_reader: EventReader<CollisionStarted>,
+    mut collision_ended_reader: EventReader<CollisionEnded>,
+    mut drain_areas: Query<&mut DrainArea>,
+    player: Query<(), With<Player>>,
+) {
+    let started = collision_started_reader
+        .read()
+        .map(|event| (event.entity, event.colliding_entity, true));
+    let ended = collision_ended_reader
+        .read()
+        .map(|event| (event.entity, event.colliding_entity, false));
+
+    for (entity, colliding_entity, is_occupied) in started.chain(ended) {
+        if !player.contains(entity) {
+            continue;
+        }
+
+        if let Ok(mut drain_area) = drain_areas.get_mut(colliding_entity) {
+            drain_area.is_occupied = is_occupied;
+        }
+    }
+}
+
 fn drain_mana(
-    mut harvesters: Query<(&GlobalTransform, &mut Harvester)>,
-    player: Query<&GlobalTransform, With<Player>>,
+    mut harvesters: Query<&mut Harvester>,
+    drain_areas: Query<(&Parent, &DrainArea)>,
     time: Res<Time>,
     mut mana: ResMut<Mana>,
 ) {
-    let Ok(player_transform) = player.get_single() else {
-        return;
-    };
-
-    for (harvester_transform, mut harvester) in &mut harvesters {
-        let distance = player_transform
-            .translation()
-            .distance(harvester_transform.translation());
-
-        if distance < DRAIN_RADIUS {
-            let drainable_mana = DRAIN_RATE * time.delta_seconds();
-
-            if harvester.mana < drainable_mana {
-                mana.0 += harvester.mana;
-                harvester.mana = 0.0;
-            } else {
-                mana.0 += drainable_mana;
-                harvester.mana -= drainable_mana;
-            };
+    for (parent, drain_area) in drain_areas.iter() {
+        if !drain_area.is_occupied {
+            continue;
+        }
+
+        let Ok(mut harvester) = harvesters.get_mut(parent.get()) else {
+            continue;
+        };
+
+        let drainable_mana = DRAIN_RATE * time.delta_seconds();
+
+        if harvester.mana < drainable_mana {
+            mana.0 += harvester.mana;
+            harvester.mana = 0.0;
+        } else {
+            mana.0 += drainable_mana;
+            harvester.mana -= drainable_mana;
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collisions::CollisionsPlugin, schedule::test_app};
+
+    #[test]
+    fn player_drains_only_while_inside_the_drain_area() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin)
+            .insert_resource(Mana(0.0))
+            .add_systems(
+                Update,
+                (track_drain_areas, drain_mana)
+                    .chain()
+                    .in_set(InGameSet::EntityUpdates),
+            );
+
+        app.world
+            .spawn(Harvester::new(MAX_MANA, 0.0))
+            .with_children(|builder| {
+                builder.spawn((
+                    Collider::new(ColliderShape::Circle {
+                        radius: DRAIN_RADIUS,
+                    }),
+                    CollisionGroups::new(Group::NONE, Group::PLAYER),
+                    Sensor,
+                    DrainArea::default(),
+                    GlobalTransform::from_xyz(0.0, 0.0, 0.0),
+                ));
+            });
+        let player = app
+            .world
+            .spawn((
+                Player { speed: 100.0 },
+                Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                CollisionGroups::new(Group::PLAYER, Group::NONE),
+                Sensor,
+                Transform::from_xyz(130.0, 0.0, 0.0),
+                GlobalTransform::from_xyz(130.0, 0.0, 0.0),
+            ))
+            .id();
+
+        for _ in 0..10 {
+            app.update();
         }
+
+        //  the sensor never pushes the player out
+        assert_eq!(
+            app.world.get::<Transform>(player).unwrap().translation.x,
+            130.0
+        );
+        let drained = app.world.resource::<Mana>().0;
+        assert!(drained > 0.0);
+
+        app.world
+            .entity_mut(player)
+            .insert(GlobalTransform::from_xyz(150.0, 0.0, 0.0));
+        app.update();
+        app.update();
+        let drained = app.world.resource::<Mana>().0;
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<Mana>().0, drained);
     }
 }
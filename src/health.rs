@@ -1,12 +1,250 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::schedule::InGameSet;
+
+const HEALTH_BAR_HEIGHT: f32 = 4.0;
+const HEALTH_BAR_Z_OFFSET: f32 = 10.0;
+const HEALTH_BAR_BACKGROUND_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HEALTH_BAR_FILL_COLOR: Color = Color::rgb(0.8, 0.1, 0.1);
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (regenerate, apply_healing)
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(Update, (spawn_health_bars, update_health_bars).chain())
+        .add_event::<HealEvent>();
+    }
+}
 
 #[derive(Component, Debug)]
 pub struct Health {
-    pub value: f32,
+    pub current: f32,
+    pub max: f32,
 }
 
 impl Health {
-    pub fn new(value: f32) -> Self {
-        Self { value }
+    /// Starts at full health.
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    pub fn ratio(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+}
+
+/// Health recovered per second.
+#[derive(Component, Debug)]
+pub struct Regeneration {
+    pub rate: f32,
+}
+
+impl Regeneration {
+    pub fn new(rate: f32) -> Self {
+        Self { rate }
+    }
+}
+
+/// Restores health, up to its maximum.
+#[derive(Event, Debug)]
+pub struct HealEvent {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+impl HealEvent {
+    pub fn new(target: Entity, amount: f32) -> Self {
+        Self { target, amount }
+    }
+}
+
+/// Shows a world-space health bar above the entity while it is damaged.
+#[derive(Component, Debug)]
+pub struct HealthBar {
+    pub width: f32,
+    /// Height of the bar above the entity's origin.
+    pub offset: f32,
+}
+
+impl HealthBar {
+    pub fn new(width: f32, offset: f32) -> Self {
+        Self { width, offset }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct HealthBarBackground;
+
+#[derive(Component, Debug)]
+pub struct HealthBarFill;
+
+fn regenerate(
+    query: Query<(Entity, &Health, &Regeneration)>,
+    mut heal_event_writer: EventWriter<HealEvent>,
+    time: Res<Time>,
+) {
+    for (entity, health, regeneration) in query.iter() {
+        if health.is_full() {
+            continue;
+        }
+
+        heal_event_writer.send(HealEvent::new(
+            entity,
+            regeneration.rate * time.delta_seconds(),
+        ));
+    }
+}
+
+fn apply_healing(mut heal_event_reader: EventReader<HealEvent>, mut query: Query<&mut Health>) {
+    for &HealEvent { target, amount } in heal_event_reader.read() {
+        let Ok(mut health) = query.get_mut(target) else {
+            continue;
+        };
+
+        //  the dead cannot be healed back
+        if health.current <= 0.0 {
+            continue;
+        }
+
+        health.current = (health.current + amount).min(health.max);
+    }
+}
+
+fn spawn_health_bars(mut commands: Commands, query: Query<(Entity, &HealthBar), Added<HealthBar>>) {
+    for (entity, health_bar) in query.iter() {
+        commands.entity(entity).with_children(|builder| {
+            builder
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: HEALTH_BAR_BACKGROUND_COLOR,
+                            custom_size: Some(Vec2::new(health_bar.width, HEALTH_BAR_HEIGHT)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, health_bar.offset, HEALTH_BAR_Z_OFFSET),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    HealthBarBackground,
+                    Name::new("HealthBar"),
+                ))
+                .with_children(|builder| {
+                    //  anchored on the left so it shrinks towards it
+                    builder.spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: HEALTH_BAR_FILL_COLOR,
+                                custom_size: Some(Vec2::new(health_bar.width, HEALTH_BAR_HEIGHT)),
+                                anchor: Anchor::CenterLeft,
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(-health_bar.width / 2.0, 0.0, 1.0),
+                            ..default()
+                        },
+                        HealthBarFill,
+                    ));
+                });
+        });
+    }
+}
+
+fn update_health_bars(
+    health_query: Query<&Health, Changed<Health>>,
+    mut background_query: Query<(&Parent, &mut Visibility, &Children), With<HealthBarBackground>>,
+    mut fill_query: Query<&mut Transform, With<HealthBarFill>>,
+) {
+    for (parent, mut visibility, children) in background_query.iter_mut() {
+        let Ok(health) = health_query.get(parent.get()) else {
+            continue;
+        };
+
+        *visibility = if health.is_full() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut transform) = fill_query.get_mut(child) {
+                transform.scale.x = health.ratio();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::test_app;
+
+    #[test]
+    fn regeneration_heals_up_to_max_but_not_the_dead() {
+        let mut app = test_app();
+        app.add_plugins(HealthPlugin);
+
+        let mut spawn = |current: f32| {
+            app.world
+                .spawn((Health { current, max: 10.0 }, Regeneration::new(6.0)))
+                .id()
+        };
+        let wounded = spawn(5.0);
+        let dead = spawn(0.0);
+
+        let mut heal_reader = app.world.resource::<Events<HealEvent>>().get_reader();
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(heal_reader
+            .read(app.world.resource::<Events<HealEvent>>())
+            .any(|event| event.target == wounded));
+
+        let health = |app: &App, entity| app.world.get::<Health>(entity).unwrap().current;
+        assert!((health(&app, wounded) - 8.0).abs() < 0.01);
+        assert_eq!(health(&app, dead), 0.0);
+
+        for _ in 0..60 {
+            app.update();
+        }
+        assert_eq!(health(&app, wounded), 10.0);
+    }
+
+    #[test]
+    fn health_bar_shows_only_while_damaged() {
+        let mut app = test_app();
+        app.add_plugins(HealthPlugin);
+
+        let entity = app
+            .world
+            .spawn((
+                Health::new(10.0),
+                HealthBar::new(20.0, 10.0),
+                SpatialBundle::default(),
+            ))
+            .id();
+        app.update();
+
+        let bar = |app: &mut App| {
+            let (visibility, children) = app
+                .world
+                .query_filtered::<(&Visibility, &Children), With<HealthBarBackground>>()
+                .single(&app.world);
+            let scale = app.world.get::<Transform>(children[0]).unwrap().scale.x;
+            (*visibility, scale)
+        };
+        assert_eq!(bar(&mut app), (Visibility::Hidden, 1.0));
+
+        app.world.get_mut::<Health>(entity).unwrap().current = 2.5;
+        app.update();
+        assert_eq!(bar(&mut app), (Visibility::Inherited, 0.25));
     }
 }
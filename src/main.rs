@@ -2,16 +2,24 @@ mod attack;
 mod bubble;
 mod camera;
 mod collisions;
+mod damage;
 mod despawn;
 mod detection;
+mod flow_field;
 mod footman;
 mod group;
 mod harvester;
 mod health;
 mod movement;
+mod pathfinding;
 mod player;
+mod projectile;
 mod schedule;
+mod shape;
+mod spatial;
 mod state;
+mod status;
+mod steering;
 mod tower;
 mod ui;
 
@@ -21,14 +29,21 @@ use attack::AttackPlugin;
 use bubble::BubblePlugin;
 use camera::CameraPlugin;
 use collisions::CollisionsPlugin;
+use damage::DamagePlugin;
 use despawn::DespawnPlugin;
 use detection::DetectionPlugin;
+use flow_field::FlowFieldPlugin;
 use footman::FootmanPlugin;
 use harvester::HarvesterPlugin;
+use health::HealthPlugin;
 use movement::MovementPlugin;
+use pathfinding::PathfindingPlugin;
 use player::PlayerPlugin;
+use projectile::ProjectilePlugin;
 use schedule::SchedulePlugin;
 use state::StatePlugin;
+use status::StatusPlugin;
+use steering::SteeringPlugin;
 use tower::TowerPlugin;
 use ui::GameUI;
 
@@ -55,14 +70,23 @@ fn main() {
         // .add_plugins(WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::Tab)))
         .insert_resource(Mana(100.0))
         .add_plugins((
-            SchedulePlugin,
+            SchedulePlugin::default(),
             StatePlugin,
             DetectionPlugin,
             MovementPlugin,
+            PathfindingPlugin,
+            FlowFieldPlugin,
+            SteeringPlugin,
             CollisionsPlugin,
             AttackPlugin,
+            ProjectilePlugin,
+            DamagePlugin,
+            HealthPlugin,
+            StatusPlugin,
             DespawnPlugin,
             CameraPlugin,
+        ))
+        .add_plugins((
             PlayerPlugin,
             HarvesterPlugin,
             BubblePlugin,
@@ -1,14 +1,17 @@
 use bevy::prelude::*;
 
-use crate::schedule::InGameSet;
+use crate::{
+    schedule::{InGameSet, TransformInterpolation},
+    status::StatusEffects,
+};
 
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            (update_velocity, update_position)
+            FixedUpdate,
+            (update_velocity, apply_drag, limit_speed, update_position)
                 .chain()
                 .in_set(InGameSet::EntityUpdates),
         );
@@ -19,6 +22,7 @@ impl Plugin for MovementPlugin {
 pub struct KinematicBundle {
     pub velocity: Velocity,
     pub acceleration: Acceleration,
+    pub interpolation: TransformInterpolation,
 }
 
 #[derive(Component, Debug)]
@@ -43,14 +47,80 @@ impl Acceleration {
     }
 }
 
-fn update_velocity(mut query: Query<(&Acceleration, &mut Velocity)>, time: Res<Time>) {
+pub fn update_velocity(mut query: Query<(&Acceleration, &mut Velocity)>, time: Res<Time>) {
     for (acceleration, mut velocity) in query.iter_mut() {
         velocity.value += acceleration.value * time.delta_seconds();
     }
 }
 
-fn update_position(mut query: Query<(&Velocity, &mut Transform)>, time: Res<Time>) {
-    for (velocity, mut transform) in query.iter_mut() {
-        transform.translation += velocity.value * time.delta_seconds();
+/// Upper bound on the length of the velocity.
+#[derive(Component, Debug)]
+pub struct MaxSpeed {
+    pub value: f32,
+}
+
+impl MaxSpeed {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+/// Share of the velocity lost per second, like air resistance.
+#[derive(Component, Debug)]
+pub struct LinearDrag {
+    pub value: f32,
+}
+
+impl LinearDrag {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+/// Speed lost per second, bringing the entity to a full stop.
+#[derive(Component, Debug)]
+pub struct Friction {
+    pub value: f32,
+}
+
+impl Friction {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+fn apply_drag(
+    mut query: Query<(&mut Velocity, Option<&LinearDrag>, Option<&Friction>)>,
+    time: Res<Time>,
+) {
+    for (mut velocity, linear_drag, friction) in query.iter_mut() {
+        if let Some(linear_drag) = linear_drag {
+            //  exponential decay, so it does not depend on the frame rate
+            velocity.value *= (-linear_drag.value * time.delta_seconds()).exp();
+        }
+
+        if let Some(friction) = friction {
+            let speed = velocity.value.length();
+            let slowed_speed = (speed - friction.value * time.delta_seconds()).max(0.0);
+
+            velocity.value = velocity.value.normalize_or_zero() * slowed_speed;
+        }
+    }
+}
+
+fn limit_speed(mut query: Query<(&mut Velocity, &MaxSpeed)>) {
+    for (mut velocity, max_speed) in query.iter_mut() {
+        velocity.value = velocity.value.clamp_length_max(max_speed.value);
+    }
+}
+
+fn update_position(
+    mut query: Query<(&Velocity, &mut Transform, Option<&StatusEffects>)>,
+    time: Res<Time>,
+) {
+    for (velocity, mut transform, status_effects) in query.iter_mut() {
+        let speed_scale = status_effects.map_or(1.0, StatusEffects::speed_scale);
+
+        transform.translation += velocity.value * speed_scale * time.delta_seconds();
     }
 }
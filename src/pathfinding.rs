@@ -1,2 +1,346 @@
-/// probably use graph networks for world building and navigation
-/// https://docs.rs/pathfinding/latest/pathfinding/directed/edmonds_karp/index.html
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::{
+    prelude::*,
+    utils::hashbrown::{HashMap, HashSet},
+};
+
+use crate::{
+    collisions::{Collider, Sensor, Static},
+    movement::Velocity,
+    schedule::InGameSet,
+    shape::{contact, ColliderShape},
+    steering::Steering,
+};
+
+const NAV_CELL_SIZE: f32 = 32.0;
+/// Room kept around obstacles, about the radius of a footman.
+const NAV_CLEARANCE: f32 = 16.0;
+/// Upper bound on the cells expanded by one search, as the grid has no edges.
+const MAX_SEARCH_NODES: usize = 4096;
+/// How far around a blocked goal to look for a walkable cell.
+const MAX_GOAL_SEARCH_RING: i32 = 8;
+const WAYPOINT_RADIUS: f32 = 8.0;
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+const NEIGHBORS: [(IVec2, u32); 8] = [
+    (IVec2::new(1, 0), STRAIGHT_COST),
+    (IVec2::new(-1, 0), STRAIGHT_COST),
+    (IVec2::new(0, 1), STRAIGHT_COST),
+    (IVec2::new(0, -1), STRAIGHT_COST),
+    (IVec2::new(1, 1), DIAGONAL_COST),
+    (IVec2::new(1, -1), DIAGONAL_COST),
+    (IVec2::new(-1, 1), DIAGONAL_COST),
+    (IVec2::new(-1, -1), DIAGONAL_COST),
+];
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (update_nav_grid, plan_paths, follow_paths)
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        )
+        .insert_resource(NavGrid::new(NAV_CELL_SIZE));
+    }
+}
+
+/// Uniform grid of the cells blocked by `Static` colliders, rebuilt whenever they change.
+///
+/// Blocked cells are inflated by `NAV_CLEARANCE`, so units can be treated as points.
+#[derive(Resource, Debug)]
+pub struct NavGrid {
+    pub cell_size: f32,
+    blocked: HashSet<IVec2>,
+}
+
+impl NavGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            blocked: HashSet::new(),
+        }
+    }
+
+    pub fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + Vec2::splat(0.5)) * self.cell_size
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        !self.blocked.contains(&cell)
+    }
+
+    /// The cells reachable in one step from `cell`, with the cost of the step.
+    /// Diagonal steps cannot cut the corner of a blocked cell.
+    pub fn neighbors(&self, cell: IVec2, ignore_obstacles: bool) -> Vec<(IVec2, u32)> {
+        NEIGHBORS
+            .into_iter()
+            .filter(|&(offset, _)| {
+                ignore_obstacles
+                    || (self.is_walkable(cell + offset)
+                        && self.is_walkable(cell + IVec2::new(offset.x, 0))
+                        && self.is_walkable(cell + IVec2::new(0, offset.y)))
+            })
+            .map(|(offset, step_cost)| (cell + offset, step_cost))
+            .collect()
+    }
+
+    fn clear(&mut self) {
+        self.blocked.clear();
+    }
+
+    fn block(&mut self, shape: &ColliderShape, transform: &GlobalTransform) {
+        let center = transform.translation().truncate();
+        let reach = Vec2::splat(shape.bounding_radius() + NAV_CLEARANCE);
+        let (min, max) = (self.cell(center - reach), self.cell(center + reach));
+        let clearance = ColliderShape::Circle {
+            radius: NAV_CLEARANCE,
+        };
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let cell = IVec2::new(x, y);
+                let cell_transform =
+                    GlobalTransform::from_translation(self.cell_center(cell).extend(0.0));
+
+                if contact(&clearance, &cell_transform, shape, transform).is_some() {
+                    self.blocked.insert(cell);
+                }
+            }
+        }
+    }
+
+    /// The walkable cell closest to `cell`, looking in growing rings around it.
+    pub fn nearest_walkable(&self, cell: IVec2) -> Option<IVec2> {
+        if self.is_walkable(cell) {
+            return Some(cell);
+        }
+
+        for ring in 1..=MAX_GOAL_SEARCH_RING {
+            let nearest = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |y| IVec2::new(x, y)))
+                .filter(|offset| offset.x.abs() == ring || offset.y.abs() == ring)
+                .map(|offset| cell + offset)
+                .filter(|&candidate| self.is_walkable(candidate))
+                .min_by_key(|&candidate| (candidate - cell).length_squared());
+
+            if nearest.is_some() {
+                return nearest;
+            }
+        }
+
+        None
+    }
+
+    /// A* over the grid, returning the waypoints from `start` (excluded) to `goal`.
+    ///
+    /// Units standing inside a blocked cell may walk through blocked cells until they are out.
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let start_cell = self.cell(start);
+        let goal_cell = self.nearest_walkable(self.cell(goal))?;
+
+        let mut open: BinaryHeap<Reverse<(u32, i32, i32)>> = BinaryHeap::new();
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+        let mut costs: HashMap<IVec2, u32> = HashMap::new();
+        let mut expanded: usize = 0;
+
+        open.push(Reverse((
+            heuristic(start_cell, goal_cell),
+            start_cell.x,
+            start_cell.y,
+        )));
+        costs.insert(start_cell, 0);
+
+        while let Some(Reverse((_, x, y))) = open.pop() {
+            let cell = IVec2::new(x, y);
+
+            if cell == goal_cell {
+                return Some(self.reconstruct_path(&came_from, cell, goal));
+            }
+
+            expanded += 1;
+            if expanded > MAX_SEARCH_NODES {
+                return None;
+            }
+
+            let cost = costs[&cell];
+            let is_escaping = !self.is_walkable(cell);
+
+            for (next, step_cost) in self.neighbors(cell, is_escaping) {
+                let next_cost = cost + step_cost;
+
+                if costs.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((
+                    next_cost + heuristic(next, goal_cell),
+                    next.x,
+                    next.y,
+                )));
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct_path(
+        &self,
+        came_from: &HashMap<IVec2, IVec2>,
+        goal_cell: IVec2,
+        goal: Vec2,
+    ) -> Vec<Vec2> {
+        let mut cells = vec![goal_cell];
+        while let Some(&previous) = came_from.get(cells.last().unwrap()) {
+            cells.push(previous);
+        }
+        cells.reverse();
+
+        //  only keep the cells where the path turns, the start cell excluded
+        let mut waypoints: Vec<Vec2> = cells
+            .windows(3)
+            .filter(|window| window[1] - window[0] != window[2] - window[1])
+            .map(|window| self.cell_center(window[1]))
+            .collect();
+
+        //  the last leg heads straight for the goal, even if it is inside an obstacle
+        if goal_cell != self.cell(goal) {
+            waypoints.push(self.cell_center(goal_cell));
+        }
+        waypoints.push(goal);
+        waypoints
+    }
+}
+
+/// Octile distance between two cells.
+fn heuristic(from: IVec2, to: IVec2) -> u32 {
+    let delta = (to - from).abs();
+    let (straight, diagonal) = (delta.x.max(delta.y) as u32, delta.x.min(delta.y) as u32);
+
+    STRAIGHT_COST * (straight - diagonal) + DIAGONAL_COST * diagonal
+}
+
+/// Steers `Velocity` at `speed` along a path to `goal`, planned on the `NavGrid`.
+/// Units with `Steering` get the path as their desired velocity instead.
+/// Without a goal, the velocity is left alone.
+#[derive(Component, Debug)]
+pub struct PathFollower {
+    pub speed: f32,
+    pub goal: Option<Vec2>,
+    pub waypoints: Vec<Vec2>,
+    /// The goal the current waypoints were planned for.
+    planned_goal: Option<Vec2>,
+}
+
+impl PathFollower {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            goal: None,
+            waypoints: vec![],
+            planned_goal: None,
+        }
+    }
+}
+
+type StaticObstacleFilter = (With<Static>, Without<Sensor>);
+type ChangedStaticObstacleFilter = (
+    With<Static>,
+    Without<Sensor>,
+    Or<(Added<Collider>, Changed<GlobalTransform>)>,
+);
+
+pub fn update_nav_grid(
+    mut nav_grid: ResMut<NavGrid>,
+    obstacle_query: Query<(&GlobalTransform, &Collider), StaticObstacleFilter>,
+    changed_obstacle_query: Query<(), ChangedStaticObstacleFilter>,
+    mut removed_statics: RemovedComponents<Static>,
+) {
+    let is_removed = removed_statics.read().count() > 0;
+
+    if !is_removed && changed_obstacle_query.is_empty() {
+        return;
+    }
+
+    nav_grid.clear();
+
+    for (transform, collider) in obstacle_query.iter() {
+        nav_grid.block(&collider.shape, transform);
+    }
+}
+
+pub fn plan_paths(nav_grid: Res<NavGrid>, mut query: Query<(&GlobalTransform, &mut PathFollower)>) {
+    for (transform, mut path_follower) in query.iter_mut() {
+        let Some(goal) = path_follower.goal else {
+            if path_follower.planned_goal.is_some() {
+                path_follower.waypoints.clear();
+                path_follower.planned_goal = None;
+            }
+            continue;
+        };
+
+        //  replan when the obstacles change or the goal moved to another cell
+        let is_outdated = nav_grid.is_changed()
+            || path_follower
+                .planned_goal
+                .is_none_or(|planned_goal| planned_goal.distance(goal) > nav_grid.cell_size);
+
+        if !is_outdated {
+            if let Some(last_waypoint) = path_follower.waypoints.last_mut() {
+                *last_waypoint = goal;
+            }
+            continue;
+        }
+
+        //  without a path, head straight for the goal and let collisions sort it out
+        let position = transform.translation().truncate();
+        path_follower.waypoints = nav_grid
+            .find_path(position, goal)
+            .unwrap_or_else(|| vec![goal]);
+        path_follower.planned_goal = Some(goal);
+    }
+}
+
+pub fn follow_paths(
+    mut query: Query<(
+        &GlobalTransform,
+        &mut PathFollower,
+        &mut Velocity,
+        Option<&mut Steering>,
+    )>,
+) {
+    for (transform, mut path_follower, mut velocity, steering) in query.iter_mut() {
+        if path_follower.goal.is_none() {
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+
+        while path_follower.waypoints.len() > 1
+            && position.distance(path_follower.waypoints[0]) <= WAYPOINT_RADIUS
+        {
+            path_follower.waypoints.remove(0);
+        }
+
+        let Some(&next_waypoint) = path_follower.waypoints.first() else {
+            continue;
+        };
+
+        let path_velocity = (next_waypoint - position).normalize_or_zero() * path_follower.speed;
+
+        match steering {
+            Some(mut steering) => steering.desired_velocity = path_velocity,
+            None => velocity.value = path_velocity.extend(0.0),
+        }
+    }
+}
@@ -1,14 +1,23 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::InspectorOptions;
 
-use crate::schedule::InGameSet;
+use crate::{
+    collisions::{Collider, CollisionGroups, Sensor},
+    group::Group,
+    schedule::{InGameSet, TransformInterpolation},
+    shape::ColliderShape,
+};
+
+const COLLIDER_RADIUS: f32 = 16.0;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_player)
-            .add_systems(Update, (character_movement).in_set(InGameSet::UserInput));
+        app.add_systems(Startup, spawn_player).add_systems(
+            FixedUpdate,
+            (character_movement).in_set(InGameSet::UserInput),
+        );
     }
 }
 
@@ -26,7 +35,14 @@ fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
             texture,
             ..default()
         },
+        Collider::new(ColliderShape::Circle {
+            radius: COLLIDER_RADIUS,
+        }),
+        CollisionGroups::new(Group::PLAYER, Group::NONE),
+        //  only there to be found by sensors, nothing should push the player around
+        Sensor,
         Player { speed: 100.0 },
+        TransformInterpolation::default(),
         Name::new("Player"),
     ));
 }
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    attack::Attack,
+    collisions::{
+        apply_collision_damage, Collider, CollisionDamage, CollisionDamageMode, CollisionGroups,
+        FastMover, Sensor,
+    },
+    damage::{AreaDamage, AreaDamageEvent},
+    group::Group,
+    movement::{Acceleration, KinematicBundle, Velocity},
+    schedule::{InGameSet, TransformInterpolation},
+    shape::ColliderShape,
+};
+
+const LIFETIME: f32 = 3.0;
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                expire_projectiles,
+                despawn_spent_projectiles.after(apply_collision_damage),
+            )
+                .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+/// Makes an `Attack` fire a projectile at its target instead of hitting it directly.
+#[derive(Component, Debug)]
+pub struct ProjectileAttack {
+    pub texture: Handle<Image>,
+    pub speed: f32,
+    pub radius: f32,
+    /// Splash dealt around the point of impact, on top of the direct hit.
+    pub area_damage: Option<AreaDamage>,
+}
+
+/// A fired projectile, spent on its first hit or once its lifetime runs out.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    /// Whoever fired it, credited with its damage.
+    pub owner: Entity,
+    pub lifetime: Timer,
+    /// Set by the first hit, after which it deals no more damage.
+    pub is_spent: bool,
+}
+
+/// Fires a projectile from `origin` towards `target`, hitting members of `filters`
+/// with the `attack` of its `owner`.
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    owner: Entity,
+    projectile_attack: &ProjectileAttack,
+    attack: &Attack,
+    origin: Vec3,
+    target: Vec3,
+    filters: Group,
+) {
+    let direction = (target - origin).truncate().normalize_or_zero();
+
+    let mut projectile = commands.spawn((
+        SpriteBundle {
+            texture: projectile_attack.texture.clone(),
+            transform: Transform {
+                translation: origin,
+                rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+                ..default()
+            },
+            ..default()
+        },
+        KinematicBundle {
+            velocity: Velocity::new(direction.extend(0.0) * projectile_attack.speed),
+            acceleration: Acceleration::new(Vec3::ZERO),
+            interpolation: TransformInterpolation::default(),
+        },
+        Collider::new(ColliderShape::Circle {
+            radius: projectile_attack.radius,
+        }),
+        CollisionGroups::new(Group::PROJECTILE, filters),
+        CollisionDamage::new(attack.amount, attack.kind, CollisionDamageMode::OnEnter),
+        Sensor,
+        FastMover::default(),
+        Projectile {
+            owner,
+            lifetime: Timer::from_seconds(LIFETIME, TimerMode::Once),
+            is_spent: false,
+        },
+        Name::new("Projectile"),
+    ));
+
+    if let Some(area_damage) = projectile_attack.area_damage {
+        projectile.insert(area_damage);
+    }
+}
+
+fn expire_projectiles(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Projectile,
+        &GlobalTransform,
+        Option<&AreaDamage>,
+    )>,
+    mut area_damage_event_writer: EventWriter<AreaDamageEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut projectile, transform, area_damage) in query.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+
+        if projectile.lifetime.just_finished() {
+            //  projectiles that miss still land somewhere
+            if let Some(&area_damage) = area_damage {
+                area_damage_event_writer.send(AreaDamageEvent::new(
+                    projectile.owner,
+                    transform.translation().truncate(),
+                    area_damage,
+                ));
+            }
+
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_spent_projectiles(
+    mut commands: Commands,
+    query: Query<(Entity, &Projectile, &GlobalTransform, Option<&AreaDamage>)>,
+    mut area_damage_event_writer: EventWriter<AreaDamageEvent>,
+) {
+    for (entity, projectile, transform, area_damage) in query.iter() {
+        if !projectile.is_spent {
+            continue;
+        }
+
+        if let Some(&area_damage) = area_damage {
+            area_damage_event_writer.send(AreaDamageEvent::new(
+                projectile.owner,
+                transform.translation().truncate(),
+                area_damage,
+            ));
+        }
+
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+    use crate::{
+        attack::{Attack, AttackOccurance, AttackPlugin},
+        collisions::CollisionsPlugin,
+        damage::{DamageEvent, DamageKind, DamagePlugin},
+        detection::DetectionGroups,
+        health::Health,
+        movement::MovementPlugin,
+        schedule::test_app,
+    };
+
+    #[test]
+    fn projectile_hits_only_the_first_body_it_reaches() {
+        let mut app = test_app();
+        app.add_plugins((
+            MovementPlugin,
+            CollisionsPlugin,
+            AttackPlugin,
+            DamagePlugin,
+            ProjectilePlugin,
+        ));
+
+        //  two bodies on the same spot, both touched on the same pass
+        let mut spawn_target = || {
+            app.world
+                .spawn((
+                    TransformBundle::from_transform(Transform::from_xyz(200.0, 0.0, 0.0)),
+                    Collider::new(ColliderShape::Circle { radius: 16.0 }),
+                    CollisionGroups::new(Group::ALLY, Group::NONE),
+                    Health::new(10.0),
+                ))
+                .id()
+        };
+        let target = spawn_target();
+        let bystander = spawn_target();
+        let shooter = app
+            .world
+            .spawn((
+                TransformBundle::default(),
+                Attack::new(
+                    3.0,
+                    DamageKind::Pierce,
+                    400.0,
+                    Timer::from_seconds(0.5, TimerMode::Repeating),
+                ),
+                ProjectileAttack {
+                    texture: Handle::default(),
+                    speed: 600.0,
+                    radius: 4.0,
+                    area_damage: None,
+                },
+                DetectionGroups::new(Group::ENEMY, Group::ALLY),
+            ))
+            .id();
+        app.world
+            .entity_mut(shooter)
+            .insert(AttackOccurance::new(shooter, target));
+
+        //  fired after half a second, landing a third of a second later,
+        //  and done before the next shot
+        let mut damage_reader = ManualEventReader::<DamageEvent>::default();
+        let mut sources = vec![];
+        for _ in 0..55 {
+            app.update();
+            sources.extend(
+                damage_reader
+                    .read(app.world.resource::<Events<DamageEvent>>())
+                    .map(|event| event.source),
+            );
+        }
+        //  credited to the shooter rather than the projectile
+        assert_eq!(sources, vec![shooter]);
+
+        let health = |entity| app.world.get::<Health>(entity).unwrap().current;
+        assert_eq!(health(target) + health(bystander), 17.0);
+        assert_eq!(app.world.query::<&Projectile>().iter(&app.world).count(), 0);
+    }
+}
@@ -1,13 +1,40 @@
-use bevy::prelude::*;
+#[cfg(test)]
+use std::time::Duration;
 
+#[cfg(test)]
+use bevy::{app::FixedMain, time::TimeUpdateStrategy};
+use bevy::{
+    app::RunFixedMainLoop,
+    prelude::*,
+    time::run_fixed_main_schedule,
+    transform::systems::{propagate_transforms, sync_simple_transforms},
+};
+
+#[cfg(test)]
+use crate::damage::{AreaDamageEvent, DamageDealt, DamageEvent, EntityDied};
 use crate::state::GameState;
 
-pub struct SchedulePlugin;
+/// Simulation ticks per second.
+const DEFAULT_TICK_RATE: f64 = 60.0;
+
+/// Runs the `InGameSet` sets in `FixedUpdate`, at `tick_rate` ticks per second,
+/// so the simulation does not depend on the frame rate.
+pub struct SchedulePlugin {
+    pub tick_rate: f64,
+}
+
+impl Default for SchedulePlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
 
 impl Plugin for SchedulePlugin {
     fn build(&self, app: &mut App) {
         app.configure_sets(
-            Update,
+            FixedUpdate,
             (
                 InGameSet::DespawnEntities,
                 InGameSet::UserInput,
@@ -17,12 +44,29 @@ impl Plugin for SchedulePlugin {
                 .chain()
                 .run_if(in_state(GameState::InGame)),
         )
+        .configure_sets(Update, InputSet.run_if(in_state(GameState::InGame)))
         .add_systems(
-            Update,
-            apply_deferred
-                .after(InGameSet::DespawnEntities)
-                .before(InGameSet::UserInput),
-        );
+            FixedUpdate,
+            (
+                (
+                    restore_transforms,
+                    sync_simple_transforms,
+                    propagate_transforms,
+                )
+                    .chain()
+                    .before(InGameSet::DespawnEntities),
+                apply_deferred
+                    .after(InGameSet::DespawnEntities)
+                    .before(InGameSet::UserInput),
+                record_transforms.after(InGameSet::CollisionDetection),
+            ),
+        )
+        //  right after the ticks, so everything in `Update` sees the rendered transforms
+        .add_systems(
+            RunFixedMainLoop,
+            interpolate_transforms.after(run_fixed_main_schedule),
+        )
+        .insert_resource(Time::<Fixed>::from_hz(self.tick_rate));
     }
 }
 
@@ -33,3 +77,161 @@ pub enum InGameSet {
     CollisionDetection,
     DespawnEntities,
 }
+
+/// Reacts to key presses in `Update`, as they last a single frame,
+/// which may run no simulation tick or several.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct InputSet;
+
+/// Renders the entity between its positions at the last two ticks,
+/// rather than jumping from one to the next.
+///
+/// Translations written outside the simulation, e.g. teleports, are kept
+/// and snapped to instead.
+#[derive(Component, Debug, Default)]
+pub struct TransformInterpolation {
+    previous: Option<Vec3>,
+    current: Option<Vec3>,
+    /// The translation last rendered, unless a tick ran since.
+    rendered: Option<Vec3>,
+}
+
+/// Puts back the simulated translations before a tick, and refreshes the global
+/// transforms, so the tick never sees where the entities were rendered.
+fn restore_transforms(mut query: Query<(&mut Transform, &mut TransformInterpolation)>) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        let Some(current) = interpolation.current else {
+            continue;
+        };
+        let rendered = interpolation.rendered.take();
+
+        //  moved since it was rendered, so start over from there
+        if rendered.is_some_and(|rendered| rendered != transform.translation) {
+            interpolation.previous = Some(transform.translation);
+            interpolation.current = Some(transform.translation);
+            continue;
+        }
+
+        transform.translation = current;
+        interpolation.previous = Some(current);
+    }
+}
+
+fn record_transforms(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in query.iter_mut() {
+        interpolation.current = Some(transform.translation);
+
+        if interpolation.previous.is_none() {
+            interpolation.previous = Some(transform.translation);
+        }
+    }
+}
+
+fn interpolate_transforms(
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+
+        transform.translation = previous.lerp(current, fixed_time.overstep_fraction());
+        interpolation.rendered = Some(transform.translation);
+    }
+}
+
+/// Runs a single simulation tick right away, whatever the time elapsed.
+#[cfg(test)]
+pub fn step_simulation(world: &mut World) {
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+    world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
+
+/// Headless app where every update runs exactly one simulation tick.
+/// Events sent across plugins are registered up front, so tests only add the plugins they need.
+#[cfg(test)]
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TransformPlugin, SchedulePlugin::default()))
+        .insert_state(GameState::InGame)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / DEFAULT_TICK_RATE,
+        )))
+        .add_event::<AreaDamageEvent>()
+        .add_event::<DamageEvent>()
+        .add_event::<DamageDealt>()
+        .add_event::<EntityDied>();
+
+    //  otherwise the first update measures no time and runs no tick
+    app.world
+        .resource_mut::<Time<Real>>()
+        .update_with_duration(Duration::ZERO);
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movement::{Acceleration, KinematicBundle, MovementPlugin, Velocity};
+
+    fn spawn_mover(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                TransformBundle::default(),
+                KinematicBundle {
+                    velocity: Velocity::new(Vec3::new(30.0, 0.0, 0.0)),
+                    acceleration: Acceleration::new(Vec3::new(0.0, 60.0, 0.0)),
+                    interpolation: TransformInterpolation::default(),
+                },
+            ))
+            .id()
+    }
+
+    fn simulate(ticks: usize) -> Vec3 {
+        let mut app = test_app();
+        app.add_plugins(MovementPlugin);
+        let entity = spawn_mover(&mut app);
+
+        for _ in 0..ticks {
+            step_simulation(&mut app.world);
+        }
+
+        app.world.get::<Transform>(entity).unwrap().translation
+    }
+
+    #[test]
+    fn stepping_is_deterministic() {
+        let position = simulate(60);
+
+        assert_eq!(position, simulate(60));
+
+        //  one second of ticks, whatever the frame rate of the test
+        assert!((position.x - 30.0).abs() < 1e-3);
+        assert!((position.y - 30.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn teleports_are_kept_by_the_next_tick() {
+        let mut app = test_app();
+        app.add_plugins(MovementPlugin);
+        let entity = spawn_mover(&mut app);
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let teleport = Vec3::new(500.0, -200.0, 0.0);
+        app.world.get_mut::<Transform>(entity).unwrap().translation = teleport;
+        app.update();
+
+        //  moved on from where it was put, rather than from where it was simulated
+        let position = app.world.get::<Transform>(entity).unwrap().translation;
+        assert!(position.distance(teleport) < 5.0);
+    }
+}
@@ -0,0 +1,448 @@
+use bevy::prelude::*;
+
+/// Geometry of a collider, in the entity's local space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    Circle {
+        radius: f32,
+    },
+    /// A box that ignores the entity's rotation.
+    Aabb {
+        half_extents: Vec2,
+    },
+}
+
+impl ColliderShape {
+    /// Radius of the smallest circle around the entity's origin containing the shape.
+    pub fn bounding_radius(&self) -> f32 {
+        match *self {
+            ColliderShape::Circle { radius } => radius,
+            ColliderShape::Aabb { half_extents } => half_extents.length(),
+        }
+    }
+
+    /// Distance from `point` to the closest point of the shape, zero inside it.
+    pub fn distance_to_point(&self, transform: &GlobalTransform, point: Vec2) -> f32 {
+        let offset = point - transform.translation().truncate();
+
+        match *self {
+            ColliderShape::Circle { radius } => (offset.length() - radius).max(0.0),
+            ColliderShape::Aabb { half_extents } => {
+                (offset.abs() - half_extents).max(Vec2::ZERO).length()
+            }
+        }
+    }
+
+    /// The shape as a convex polygon (a point or a box) inflated by a radius.
+    fn rounded_polygon(&self, transform: &GlobalTransform) -> (Vec<Vec2>, f32) {
+        let center = transform.translation().truncate();
+
+        match *self {
+            ColliderShape::Circle { radius } => (vec![center], radius),
+            ColliderShape::Aabb { half_extents } => {
+                (box_vertices(center, Vec2::X, half_extents), 0.0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Contact {
+    /// Unit vector pointing from the first shape towards the second.
+    pub normal: Vec2,
+    /// How far the second shape must move along `normal` to stop overlapping.
+    pub depth: f32,
+}
+
+/// Separating axis test between two shapes, returning the smallest penetration if they overlap.
+pub fn contact(
+    shape_a: &ColliderShape,
+    transform_a: &GlobalTransform,
+    shape_b: &ColliderShape,
+    transform_b: &GlobalTransform,
+) -> Option<Contact> {
+    let (vertices_a, radius_a) = shape_a.rounded_polygon(transform_a);
+    let (vertices_b, radius_b) = shape_b.rounded_polygon(transform_b);
+
+    let mut axes = edge_normals(&vertices_a);
+    axes.extend(edge_normals(&vertices_b));
+
+    //  rounded corners and ends meet along the line between their vertices
+    for &vertex_a in vertices_a.iter() {
+        for &vertex_b in vertices_b.iter() {
+            if let Some(axis) = (vertex_b - vertex_a).try_normalize() {
+                axes.push(axis);
+            }
+        }
+    }
+
+    //  concentric circles have no axis of their own, any one will do
+    if axes.is_empty() {
+        axes.push(Vec2::X);
+    }
+
+    let mut best: Option<Contact> = None;
+
+    for axis in axes {
+        let (min_a, max_a) = project(&vertices_a, radius_a, axis);
+        let (min_b, max_b) = project(&vertices_b, radius_b, axis);
+
+        //  push b forwards or backwards along the axis, whichever is shorter
+        let forward = max_a - min_b;
+        let backward = max_b - min_a;
+
+        if forward <= 0.0 || backward <= 0.0 {
+            return None;
+        }
+
+        let candidate = if forward < backward {
+            Contact {
+                normal: axis,
+                depth: forward,
+            }
+        } else {
+            Contact {
+                normal: -axis,
+                depth: backward,
+            }
+        };
+
+        let is_shallower = match best {
+            Some(contact) => candidate.depth < contact.depth,
+            None => true,
+        };
+
+        if is_shallower {
+            best = Some(candidate);
+        }
+    }
+
+    best
+}
+
+/// Earliest time in `0..=1` at which a circle travelling from `start` to `end` touches
+/// a circle resting at the origin, `radius` being the sum of both radii.
+pub fn time_of_impact(start: Vec2, end: Vec2, radius: f32) -> Option<f32> {
+    let direction = end - start;
+    let a = direction.length_squared();
+    let b = 2.0 * start.dot(direction);
+    let c = start.length_squared() - radius * radius;
+
+    //  already touching at the start, so nothing was tunnelled through
+    if c <= 0.0 || a == 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let toi = (-b - discriminant.sqrt()) / (2.0 * a);
+
+    (0.0..=1.0).contains(&toi).then_some(toi)
+}
+
+/// Distance along the unit `direction` at which `shape`, moving from `origin`, first touches
+/// `target`, if that happens within `max_distance`.
+pub fn cast(
+    shape: &ColliderShape,
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    target: &ColliderShape,
+    target_transform: &GlobalTransform,
+) -> Option<f32> {
+    let origin_transform = GlobalTransform::from_translation(origin.extend(0.0));
+
+    if contact(shape, &origin_transform, target, target_transform).is_some() {
+        return Some(0.0);
+    }
+
+    let (cast_vertices, cast_radius) = shape.rounded_polygon(&origin_transform);
+    let (target_vertices, target_radius) = target.rounded_polygon(target_transform);
+    let radius = cast_radius + target_radius;
+
+    //  every origin at which the shapes touch forms one rounded polygon (a minkowski difference)
+    let mut points: Vec<Vec2> = vec![];
+    for &target_vertex in target_vertices.iter() {
+        for &cast_vertex in cast_vertices.iter() {
+            points.push(target_vertex - (cast_vertex - origin));
+        }
+    }
+    let hull = convex_hull(points);
+
+    //  the ray enters it either through a rounded corner or a flat side
+    let corner_hits = hull
+        .iter()
+        .filter_map(|&vertex| ray_circle(origin, direction, vertex, radius));
+    let side_hits = hull
+        .iter()
+        .zip(hull.iter().cycle().skip(1))
+        .filter_map(|(&start, &end)| {
+            let outward = -(end - start).perp().try_normalize()?;
+            ray_segment(
+                origin,
+                direction,
+                start + outward * radius,
+                end + outward * radius,
+            )
+        });
+
+    corner_hits
+        .chain(side_hits)
+        .filter(|&distance| distance <= max_distance)
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+fn ray_circle(origin: Vec2, direction: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = origin - center;
+    let b = offset.dot(direction);
+    let c = offset.length_squared() - radius * radius;
+    let discriminant = b * b - c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let distance = -b - discriminant.sqrt();
+
+    (distance >= 0.0).then_some(distance)
+}
+
+fn ray_segment(origin: Vec2, direction: Vec2, start: Vec2, end: Vec2) -> Option<f32> {
+    let edge = end - start;
+    let denominator = direction.perp_dot(edge);
+
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let to_start = start - origin;
+    let distance = to_start.perp_dot(edge) / denominator;
+    let along_edge = to_start.perp_dot(direction) / denominator;
+
+    (distance >= 0.0 && (0.0..=1.0).contains(&along_edge)).then_some(distance)
+}
+
+/// Counter-clockwise hull of the points (monotone chain).
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<Vec2> = vec![];
+
+    //  lower half, then upper half
+    for pass in [points.clone(), points.iter().rev().copied().collect()] {
+        let start = hull.len();
+
+        for point in pass {
+            while hull.len() >= start + 2
+                && (hull[hull.len() - 1] - hull[hull.len() - 2])
+                    .perp_dot(point - hull[hull.len() - 2])
+                    <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+
+        //  the last point starts the other half
+        hull.pop();
+    }
+
+    hull
+}
+
+fn box_vertices(center: Vec2, axis_x: Vec2, half_extents: Vec2) -> Vec<Vec2> {
+    let x = axis_x * half_extents.x;
+    let y = axis_x.perp() * half_extents.y;
+
+    vec![
+        center - x - y,
+        center + x - y,
+        center + x + y,
+        center - x + y,
+    ]
+}
+
+fn edge_normals(vertices: &[Vec2]) -> Vec<Vec2> {
+    if vertices.len() < 2 {
+        return vec![];
+    }
+
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .filter_map(|(&start, &end)| (end - start).perp().try_normalize())
+        .collect()
+}
+
+fn project(vertices: &[Vec2], radius: f32, axis: Vec2) -> (f32, f32) {
+    let (min, max) = vertices
+        .iter()
+        .map(|vertex| vertex.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+
+    (min - radius, max + radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn at(x: f32, y: f32) -> GlobalTransform {
+        GlobalTransform::from_translation(Vec3::new(x, y, 0.0))
+    }
+
+    fn assert_contact(contact: Option<Contact>, normal: Vec2, depth: f32) {
+        let contact = contact.expect("shapes should overlap");
+
+        assert!(
+            contact.normal.abs_diff_eq(normal, EPSILON),
+            "normal {:?} != {:?}",
+            contact.normal,
+            normal
+        );
+        assert!(
+            (contact.depth - depth).abs() < EPSILON,
+            "depth {} != {}",
+            contact.depth,
+            depth
+        );
+    }
+
+    #[test]
+    fn circle_circle_contact() {
+        let circle = ColliderShape::Circle { radius: 10.0 };
+
+        assert_contact(
+            contact(&circle, &at(0.0, 0.0), &circle, &at(15.0, 0.0)),
+            Vec2::X,
+            5.0,
+        );
+        assert_contact(
+            contact(&circle, &at(15.0, 0.0), &circle, &at(0.0, 0.0)),
+            Vec2::NEG_X,
+            5.0,
+        );
+        assert!(contact(&circle, &at(0.0, 0.0), &circle, &at(25.0, 0.0)).is_none());
+
+        //  dead center, any normal will do as long as they are pushed fully apart
+        let centered = contact(&circle, &at(0.0, 0.0), &circle, &at(0.0, 0.0));
+        assert!((centered.unwrap().depth - 20.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn circle_aabb_contact() {
+        let circle = ColliderShape::Circle { radius: 10.0 };
+        let aabb = ColliderShape::Aabb {
+            half_extents: Vec2::splat(10.0),
+        };
+
+        assert_contact(
+            contact(&circle, &at(0.0, 0.0), &aabb, &at(15.0, 0.0)),
+            Vec2::X,
+            5.0,
+        );
+
+        //  near the corner, only the rounded part of the circle can touch
+        assert!(contact(&circle, &at(0.0, 0.0), &aabb, &at(18.0, 18.0)).is_none());
+    }
+
+    #[test]
+    fn aabb_aabb_contact() {
+        let aabb = ColliderShape::Aabb {
+            half_extents: Vec2::splat(10.0),
+        };
+
+        assert_contact(
+            contact(&aabb, &at(0.0, 0.0), &aabb, &at(2.0, 15.0)),
+            Vec2::Y,
+            5.0,
+        );
+        assert!(contact(&aabb, &at(0.0, 0.0), &aabb, &at(0.0, 25.0)).is_none());
+    }
+
+    #[test]
+    fn distance_to_point_reaches_the_surface() {
+        let circle = ColliderShape::Circle { radius: 10.0 };
+        let aabb = ColliderShape::Aabb {
+            half_extents: Vec2::new(10.0, 50.0),
+        };
+
+        assert_eq!(
+            circle.distance_to_point(&at(0.0, 0.0), Vec2::new(30.0, 0.0)),
+            20.0
+        );
+        assert_eq!(
+            circle.distance_to_point(&at(0.0, 0.0), Vec2::new(5.0, 0.0)),
+            0.0
+        );
+
+        assert_eq!(
+            aabb.distance_to_point(&at(0.0, 0.0), Vec2::new(30.0, 40.0)),
+            20.0
+        );
+        assert_eq!(
+            aabb.distance_to_point(&at(0.0, 0.0), Vec2::new(13.0, 54.0)),
+            5.0
+        );
+        assert_eq!(
+            aabb.distance_to_point(&at(0.0, 0.0), Vec2::new(0.0, 0.0)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn cast_hits_and_misses() {
+        let circle = ColliderShape::Circle { radius: 5.0 };
+        let target = ColliderShape::Circle { radius: 10.0 };
+        let aabb = ColliderShape::Aabb {
+            half_extents: Vec2::splat(10.0),
+        };
+        let origin = Vec2::new(-50.0, 0.0);
+
+        let distance = cast(&circle, origin, Vec2::X, 100.0, &target, &at(0.0, 0.0));
+        assert!((distance.unwrap() - 35.0).abs() < EPSILON);
+
+        let distance = cast(&circle, origin, Vec2::X, 100.0, &aabb, &at(0.0, 0.0));
+        assert!((distance.unwrap() - 35.0).abs() < EPSILON);
+
+        //  a ray is a cast of a point
+        let point = ColliderShape::Circle { radius: 0.0 };
+        let distance = cast(&point, origin, Vec2::X, 100.0, &aabb, &at(0.0, 0.0));
+        assert!((distance.unwrap() - 40.0).abs() < EPSILON);
+
+        //  too short, pointing away, or passing by
+        assert!(cast(&circle, origin, Vec2::X, 20.0, &target, &at(0.0, 0.0)).is_none());
+        assert!(cast(&circle, origin, Vec2::NEG_X, 100.0, &target, &at(0.0, 0.0)).is_none());
+        assert!(cast(&circle, origin, Vec2::X, 100.0, &target, &at(0.0, 20.0)).is_none());
+
+        //  already overlapping
+        assert_eq!(
+            cast(&circle, Vec2::ZERO, Vec2::X, 100.0, &target, &at(0.0, 0.0)),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn time_of_impact_catches_tunnelling() {
+        //  both ends are clear of the circle, but the path goes right through it
+        let toi = time_of_impact(Vec2::new(-30.0, 0.0), Vec2::new(30.0, 0.0), 10.0);
+        assert!((toi.unwrap() - 1.0 / 3.0).abs() < EPSILON);
+
+        assert!(time_of_impact(Vec2::new(-30.0, 20.0), Vec2::new(30.0, 20.0), 10.0).is_none());
+        assert!(time_of_impact(Vec2::new(-30.0, 0.0), Vec2::new(-20.0, 0.0), 10.0).is_none());
+        assert!(time_of_impact(Vec2::new(-5.0, 0.0), Vec2::new(30.0, 0.0), 10.0).is_none());
+    }
+}
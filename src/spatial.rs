@@ -0,0 +1,319 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::hashbrown::HashMap};
+
+use crate::{
+    collisions::{swept_bounds, Collider, CollisionGroups, FastMover},
+    group::Group,
+    shape::{cast, contact, ColliderShape},
+};
+
+/// Uniform grid used as the collision broadphase.
+///
+/// Every collider is inserted into each cell its bounding square overlaps,
+/// so two colliders can only touch if they share at least one cell.
+#[derive(Resource, Debug)]
+pub struct SpatialHash {
+    pub cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2, radius: f32) {
+        let (min, max) = self.cell_range(position, radius);
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells
+                    .entry(IVec2::new(x, y))
+                    .or_insert_with(Vec::new)
+                    .push(entity);
+            }
+        }
+    }
+
+    /// Returns every entity sharing a cell with the given circle, without duplicates.
+    pub fn query(&self, position: Vec2, radius: f32) -> Vec<Entity> {
+        let (min, max) = self.cell_range(position, radius);
+        let mut entities: Vec<Entity> = vec![];
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+                    entities.extend(cell.iter().copied());
+                }
+            }
+        }
+
+        //  large colliders span several cells
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    fn cell_range(&self, position: Vec2, radius: f32) -> (IVec2, IVec2) {
+        (
+            self.cell(position - Vec2::splat(radius)),
+            self.cell(position + Vec2::splat(radius)),
+        )
+    }
+}
+
+/// Read-only queries against the colliders, sharing the collision broadphase.
+///
+/// Every query takes a `filter`; only colliders whose `CollisionGroups` memberships
+/// share a bit with it are returned.
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    spatial_hash: Res<'w, SpatialHash>,
+    colliders: Query<
+        'w,
+        's,
+        (
+            &'static GlobalTransform,
+            &'static Collider,
+            &'static CollisionGroups,
+        ),
+    >,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CastHit {
+    pub entity: Entity,
+    pub distance: f32,
+}
+
+impl SpatialQuery<'_, '_> {
+    /// Every collider overlapping the circle.
+    pub fn overlap_circle(&self, center: Vec2, radius: f32, filter: Group) -> Vec<Entity> {
+        let circle = ColliderShape::Circle { radius };
+        let circle_transform = GlobalTransform::from_translation(center.extend(0.0));
+
+        self.spatial_hash
+            .query(center, radius)
+            .into_iter()
+            .filter(|&entity| {
+                let Ok((transform, collider, groups)) = self.colliders.get(entity) else {
+                    return false;
+                };
+
+                (groups.memberships & filter) != Group::NONE
+                    && contact(&circle, &circle_transform, &collider.shape, transform).is_some()
+            })
+            .collect()
+    }
+
+    /// Every collider hit along the ray, nearest first.
+    pub fn cast_ray(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        filter: Group,
+    ) -> Vec<CastHit> {
+        let point = ColliderShape::Circle { radius: 0.0 };
+
+        self.cast_shape(&point, origin, direction, max_distance, filter)
+    }
+
+    /// Every collider hit by `shape` when moved along the ray without rotating, nearest first.
+    pub fn cast_shape(
+        &self,
+        shape: &ColliderShape,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        filter: Group,
+    ) -> Vec<CastHit> {
+        let Some(direction) = direction.try_normalize() else {
+            return vec![];
+        };
+        let (center, radius) = swept_bounds(
+            (origin, origin + direction * max_distance),
+            shape.bounding_radius(),
+        );
+
+        let mut hits: Vec<CastHit> = self
+            .spatial_hash
+            .query(center, radius)
+            .into_iter()
+            .filter_map(|entity| {
+                let (transform, collider, groups) = self.colliders.get(entity).ok()?;
+
+                if (groups.memberships & filter) == Group::NONE {
+                    return None;
+                }
+
+                let distance = cast(
+                    shape,
+                    origin,
+                    direction,
+                    max_distance,
+                    &collider.shape,
+                    transform,
+                )?;
+
+                Some(CastHit { entity, distance })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+pub fn update_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    query: Query<(Entity, &GlobalTransform, &Collider, Option<&FastMover>)>,
+) {
+    spatial_hash.clear();
+
+    for (entity, transform, collider, fast_mover) in query.iter() {
+        let position = transform.translation().truncate();
+
+        //  fast movers cover every cell along their path
+        let path = match fast_mover {
+            Some(fast_mover) => fast_mover.path(position),
+            None => (position, position),
+        };
+        let (center, radius) = swept_bounds(path, collider.shape.bounding_radius());
+
+        spatial_hash.insert(entity, center, radius);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{collisions::CollisionsPlugin, schedule::test_app};
+
+    #[test]
+    fn query_only_returns_colliders_in_shared_cells() {
+        let mut spatial_hash = SpatialHash::new(64.0);
+        let near = Entity::from_raw(0);
+        let far = Entity::from_raw(1);
+        let large = Entity::from_raw(2);
+
+        spatial_hash.insert(near, Vec2::new(10.0, 10.0), 8.0);
+        spatial_hash.insert(far, Vec2::new(500.0, 500.0), 8.0);
+        spatial_hash.insert(large, Vec2::new(100.0, 0.0), 120.0);
+
+        let entities = spatial_hash.query(Vec2::new(20.0, 20.0), 8.0);
+
+        assert_eq!(entities, vec![near, large]);
+    }
+
+    #[test]
+    fn overlap_circle_filters_by_shape_and_group() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let mut spawn = |x: f32, memberships: Group| {
+            app.world
+                .spawn((
+                    Collider::new(ColliderShape::Circle { radius: 10.0 }),
+                    CollisionGroups::new(memberships, Group::NONE),
+                    TransformBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+                ))
+                .id()
+        };
+        let inside = spawn(30.0, Group::ENEMY);
+        //  touching the circle, but outside the filter
+        spawn(30.0, Group::ALLY);
+        //  in the same broadphase cell, but not touching the circle
+        spawn(45.0, Group::ENEMY);
+
+        app.update();
+
+        let entities = app.world.run_system_once(|spatial_query: SpatialQuery| {
+            spatial_query.overlap_circle(Vec2::ZERO, 25.0, Group::ENEMY)
+        });
+
+        assert_eq!(entities, vec![inside]);
+    }
+
+    #[test]
+    fn cast_ray_returns_hits_nearest_first() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let mut spawn = |x: f32, y: f32, memberships: Group| {
+            app.world
+                .spawn((
+                    Collider::new(ColliderShape::Circle { radius: 10.0 }),
+                    CollisionGroups::new(memberships, Group::NONE),
+                    TransformBundle::from_transform(Transform::from_xyz(x, y, 0.0)),
+                ))
+                .id()
+        };
+        let far = spawn(80.0, 0.0, Group::ENEMY);
+        let near = spawn(40.0, 0.0, Group::ENEMY);
+        //  beside the ray, past its end, and outside the filter
+        spawn(40.0, 30.0, Group::ENEMY);
+        spawn(150.0, 0.0, Group::ENEMY);
+        spawn(60.0, 0.0, Group::ALLY);
+
+        app.update();
+
+        let hits = app.world.run_system_once(|spatial_query: SpatialQuery| {
+            spatial_query.cast_ray(Vec2::ZERO, Vec2::X * 5.0, 100.0, Group::ENEMY)
+        });
+
+        assert_eq!(
+            hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            vec![near, far]
+        );
+        assert!((hits[0].distance - 30.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cast_shape_hits_what_the_ray_would_miss() {
+        let mut app = test_app();
+        app.add_plugins(CollisionsPlugin);
+
+        let beside = app
+            .world
+            .spawn((
+                Collider::new(ColliderShape::Circle { radius: 10.0 }),
+                CollisionGroups::new(Group::ENEMY, Group::NONE),
+                TransformBundle::from_transform(Transform::from_xyz(40.0, 30.0, 0.0)),
+            ))
+            .id();
+
+        app.update();
+
+        let (ray_hits, shape_hits) = app.world.run_system_once(|spatial_query: SpatialQuery| {
+            let circle = ColliderShape::Circle { radius: 25.0 };
+
+            (
+                spatial_query.cast_ray(Vec2::ZERO, Vec2::X, 100.0, Group::ENEMY),
+                spatial_query.cast_shape(&circle, Vec2::ZERO, Vec2::X, 100.0, Group::ENEMY),
+            )
+        });
+
+        assert!(ray_hits.is_empty());
+        assert_eq!(
+            shape_hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            vec![beside]
+        );
+        //  the circles first touch 35 apart, with the obstacle 30 off the ray
+        let expected = 40.0 - (35.0_f32.powi(2) - 30.0_f32.powi(2)).sqrt();
+
+        assert!((shape_hits[0].distance - expected).abs() < 1e-3);
+    }
+}
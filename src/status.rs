@@ -0,0 +1,354 @@
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+
+use crate::{
+    damage::{DamageDealt, DamageEvent, DamageKind},
+    schedule::InGameSet,
+};
+
+const TICK_RATE: f32 = 1.0;
+const MAX_POISON_STACKS: usize = 5;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (apply_on_hit_effects, tick_status_effects)
+                .chain()
+                .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Reduces movement and attack speed by `magnitude` (0..=1).
+    Slow,
+    /// Deals `magnitude` magic damage every tick, stacking up to `MAX_POISON_STACKS`.
+    Poison,
+    /// Stops movement and attacks.
+    Stun,
+}
+
+impl StatusKind {
+    fn max_stacks(&self) -> usize {
+        match self {
+            StatusKind::Poison => MAX_POISON_STACKS,
+            _ => 1,
+        }
+    }
+
+    fn is_damage_over_time(&self) -> bool {
+        matches!(self, StatusKind::Poison)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub duration: Timer,
+    pub tick: Timer,
+    /// Who inflicted the effect, credited with its damage.
+    pub source: Option<Entity>,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, magnitude: f32, duration: f32) -> Self {
+        Self {
+            kind,
+            magnitude,
+            duration: Timer::from_seconds(duration, TimerMode::Once),
+            tick: Timer::from_seconds(TICK_RATE, TimerMode::Repeating),
+            source: None,
+        }
+    }
+}
+
+/// The timed effects currently affecting an entity.
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Adds a new stack of the effect while its kind allows it, otherwise refreshes
+    /// the stack closest to running out, keeping the stronger magnitude and longer duration.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let stacks = self
+            .effects
+            .iter()
+            .filter(|existing| existing.kind == effect.kind)
+            .count();
+
+        if stacks < effect.kind.max_stacks() {
+            self.effects.push(effect);
+            return;
+        }
+
+        let Some(existing) = self
+            .effects
+            .iter_mut()
+            .filter(|existing| existing.kind == effect.kind)
+            .min_by(|a, b| a.duration.remaining().cmp(&b.duration.remaining()))
+        else {
+            return;
+        };
+
+        existing.magnitude = existing.magnitude.max(effect.magnitude);
+        existing.source = effect.source;
+
+        if effect.duration.remaining() > existing.duration.remaining() {
+            existing.duration = effect.duration;
+        }
+    }
+
+    pub fn is_active(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Multiplier applied to movement and attack speed.
+    pub fn speed_scale(&self) -> f32 {
+        if self.is_active(StatusKind::Stun) {
+            return 0.0;
+        }
+
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Slow)
+            .fold(1.0, |scale, effect| {
+                scale * (1.0 - effect.magnitude.clamp(0.0, 1.0))
+            })
+    }
+}
+
+/// Status effects inflicted on whatever this entity damages directly.
+#[derive(Component, Debug)]
+pub struct OnHitEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl OnHitEffects {
+    pub fn new(effects: Vec<StatusEffect>) -> Self {
+        Self { effects }
+    }
+}
+
+fn apply_on_hit_effects(
+    mut commands: Commands,
+    mut damage_dealt_reader: EventReader<DamageDealt>,
+    source_query: Query<&OnHitEffects>,
+    mut target_query: Query<&mut StatusEffects>,
+) {
+    //  several hits may land on the same unaffected entity this frame
+    let mut new_status_effects: HashMap<Entity, StatusEffects> = HashMap::new();
+
+    //  hits stopped by a shield or invulnerability inflict nothing either
+    for DamageDealt(event) in damage_dealt_reader.read() {
+        //  damage over time does not spread effects further
+        if event.is_periodic {
+            continue;
+        }
+
+        let Ok(on_hit_effects) = source_query.get(event.source) else {
+            continue;
+        };
+
+        for effect in on_hit_effects.effects.iter() {
+            let effect = StatusEffect {
+                source: Some(event.source),
+                ..effect.clone()
+            };
+
+            match target_query.get_mut(event.target) {
+                Ok(mut status_effects) => status_effects.apply(effect),
+                Err(_) => new_status_effects
+                    .entry(event.target)
+                    .or_default()
+                    .apply(effect),
+            }
+        }
+    }
+
+    for (entity, status_effects) in new_status_effects {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(status_effects);
+        }
+    }
+}
+
+fn tick_status_effects(
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut status_effects) in query.iter_mut() {
+        for effect in status_effects.effects.iter_mut() {
+            effect.duration.tick(time.delta());
+
+            if !effect.kind.is_damage_over_time() {
+                continue;
+            }
+
+            effect.tick.tick(time.delta());
+
+            for _ in 0..effect.tick.times_finished_this_tick() {
+                damage_event_writer.send(DamageEvent::periodic(
+                    effect.source.unwrap_or(entity),
+                    entity,
+                    effect.magnitude,
+                    DamageKind::Magic,
+                ));
+            }
+        }
+
+        status_effects
+            .effects
+            .retain(|effect| !effect.duration.finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        collisions::CollisionsPlugin,
+        damage::{DamagePlugin, Invulnerable, Shield},
+        health::Health,
+        movement::{MovementPlugin, Velocity},
+        schedule::test_app,
+    };
+
+    #[test]
+    fn poison_stacks_up_to_its_limit_then_refreshes() {
+        let mut status_effects = StatusEffects::default();
+
+        for duration in 1..=MAX_POISON_STACKS {
+            status_effects.apply(StatusEffect::new(StatusKind::Poison, 1.0, duration as f32));
+        }
+        //  replaces the stack closest to running out
+        status_effects.apply(StatusEffect::new(StatusKind::Poison, 2.0, 10.0));
+        //  a single slow only ever gets refreshed, keeping the stronger magnitude
+        status_effects.apply(StatusEffect::new(StatusKind::Slow, 0.5, 1.0));
+        status_effects.apply(StatusEffect::new(StatusKind::Slow, 0.2, 2.0));
+
+        let poison: Vec<(f32, f32)> = status_effects
+            .effects
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Poison)
+            .map(|effect| (effect.magnitude, effect.duration.duration().as_secs_f32()))
+            .collect();
+        assert_eq!(
+            poison,
+            vec![(2.0, 10.0), (1.0, 2.0), (1.0, 3.0), (1.0, 4.0), (1.0, 5.0)]
+        );
+        assert_eq!(status_effects.speed_scale(), 0.5);
+
+        status_effects.apply(StatusEffect::new(StatusKind::Stun, 1.0, 1.0));
+        assert_eq!(status_effects.speed_scale(), 0.0);
+    }
+
+    #[test]
+    fn hits_inflict_slow_and_poison_over_time() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, MovementPlugin, DamagePlugin, StatusPlugin));
+
+        let source = app
+            .world
+            .spawn(OnHitEffects::new(vec![
+                StatusEffect::new(StatusKind::Slow, 0.5, 1.0),
+                StatusEffect::new(StatusKind::Poison, 1.0, 3.0),
+            ]))
+            .id();
+        let mut spawn_target = || {
+            app.world
+                .spawn((
+                    Health::new(10.0),
+                    Velocity::new(Vec3::new(60.0, 0.0, 0.0)),
+                    TransformBundle::default(),
+                ))
+                .id()
+        };
+        let target = spawn_target();
+        let bystander = spawn_target();
+
+        app.update();
+        app.world
+            .send_event(DamageEvent::new(source, target, 1.0, DamageKind::Pierce));
+        //  let the slow land first
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let x = |app: &App, entity| app.world.get::<Transform>(entity).unwrap().translation.x;
+        let start = (x(&app, target), x(&app, bystander));
+        for _ in 0..30 {
+            app.update();
+        }
+        //  half speed
+        assert_eq!(
+            x(&app, target) - start.0,
+            (x(&app, bystander) - start.1) / 2.0
+        );
+
+        for _ in 0..240 {
+            app.update();
+        }
+
+        let health = |entity| app.world.get::<Health>(entity).unwrap().current;
+        //  the hit itself, then three ticks of poison
+        assert_eq!(health(target), 6.0);
+        assert_eq!(health(bystander), 10.0);
+        assert!(app
+            .world
+            .get::<StatusEffects>(target)
+            .unwrap()
+            .effects
+            .is_empty());
+    }
+
+    #[test]
+    fn stopped_hits_inflict_no_effects() {
+        let mut app = test_app();
+        app.add_plugins((CollisionsPlugin, DamagePlugin, StatusPlugin));
+
+        let source = app
+            .world
+            .spawn(OnHitEffects::new(vec![StatusEffect::new(
+                StatusKind::Slow,
+                0.5,
+                1.0,
+            )]))
+            .id();
+        let shielded = app
+            .world
+            .spawn((
+                Health::new(10.0),
+                Shield::new(5.0, 0.0, 1.0),
+                TransformBundle::default(),
+            ))
+            .id();
+        let invulnerable = app
+            .world
+            .spawn((
+                Health::new(10.0),
+                Invulnerable::new(1.0),
+                TransformBundle::default(),
+            ))
+            .id();
+
+        app.update();
+        for target in [shielded, invulnerable] {
+            app.world
+                .send_event(DamageEvent::new(source, target, 1.0, DamageKind::Pierce));
+        }
+        app.update();
+        app.update();
+
+        for target in [shielded, invulnerable] {
+            assert_eq!(app.world.get::<Health>(target).unwrap().current, 10.0);
+            assert!(app.world.get::<StatusEffects>(target).is_none());
+        }
+    }
+}
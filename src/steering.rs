@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+
+use crate::{
+    attack::{attack_occurance, AttackOccurance},
+    collisions::{Collider, CollisionGroups, Static},
+    movement::{update_velocity, Acceleration, Velocity},
+    pathfinding::follow_paths,
+    schedule::InGameSet,
+    shape::ColliderShape,
+    spatial::SpatialQuery,
+};
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            steer
+                .after(follow_paths)
+                .after(attack_occurance)
+                .before(update_velocity)
+                .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+/// How much each behavior contributes to the steered velocity; zero turns it off.
+#[derive(Clone, Copy, Debug)]
+pub struct SteeringWeights {
+    pub separation: f32,
+    pub arrival: f32,
+    pub avoidance: f32,
+}
+
+impl SteeringWeights {
+    pub fn new(separation: f32, arrival: f32, avoidance: f32) -> Self {
+        Self {
+            separation,
+            arrival,
+            avoidance,
+        }
+    }
+}
+
+/// Accelerates the unit towards the velocity picked by whatever moves it (path following,
+/// flow fields...), keeping it away from its neighbors and obstacles, and slowing down when arriving.
+#[derive(Component, Debug)]
+pub struct Steering {
+    pub weights: SteeringWeights,
+    /// Top speed, also the strength of separation and avoidance.
+    pub speed: f32,
+    /// Largest change of velocity per second, so knockbacks take a while to recover from.
+    pub max_force: f32,
+    /// Neighbors closer than this push the unit away.
+    pub separation_radius: f32,
+    /// Distance from the stopping point at which the unit starts slowing down.
+    pub slowing_radius: f32,
+    /// How far ahead obstacles are looked for.
+    pub look_ahead: f32,
+    pub destination: Option<Vec2>,
+    /// Distance from the destination at which the unit stops.
+    pub stopping_distance: f32,
+    /// Velocity the unit would move at with nothing in its way.
+    pub desired_velocity: Vec2,
+}
+
+impl Steering {
+    pub fn new(
+        weights: SteeringWeights,
+        speed: f32,
+        max_force: f32,
+        separation_radius: f32,
+        slowing_radius: f32,
+        look_ahead: f32,
+    ) -> Self {
+        Self {
+            weights,
+            speed,
+            max_force,
+            separation_radius,
+            slowing_radius,
+            look_ahead,
+            destination: None,
+            stopping_distance: 0.0,
+            desired_velocity: Vec2::ZERO,
+        }
+    }
+
+    /// Share (0..=1) of the speed kept when `distance` away from the destination.
+    fn arrival_scale(&self, distance: f32) -> f32 {
+        let scale = ((distance - self.stopping_distance) / self.slowing_radius).clamp(0.0, 1.0);
+
+        1.0 - self.weights.arrival * (1.0 - scale)
+    }
+}
+
+type SteeringMotion = (
+    &'static Velocity,
+    &'static mut Acceleration,
+    Has<AttackOccurance>,
+);
+
+pub fn steer(
+    mut query: Query<(
+        Entity,
+        &GlobalTransform,
+        &Collider,
+        &CollisionGroups,
+        &Steering,
+        SteeringMotion,
+    )>,
+    neighbor_query: Query<&GlobalTransform, With<Steering>>,
+    obstacle_query: Query<(&GlobalTransform, &Collider), With<Static>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    //  the velocity can't change in no time
+    if time.delta_seconds() == 0.0 {
+        return;
+    }
+
+    for (
+        entity,
+        transform,
+        collider,
+        groups,
+        steering,
+        (velocity, mut acceleration, is_attacking),
+    ) in query.iter_mut()
+    {
+        //  attacking units hold their ground, the attack already stopped them
+        if is_attacking {
+            acceleration.value = Vec3::ZERO;
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+        let mut desired = steering.desired_velocity;
+
+        if let Some(destination) = steering.destination {
+            desired *= steering.arrival_scale(position.distance(destination));
+        }
+
+        //  push away from every neighbor, harder the closer it is
+        let mut separation = Vec2::ZERO;
+
+        for neighbor in
+            spatial_query.overlap_circle(position, steering.separation_radius, groups.memberships)
+        {
+            if neighbor == entity {
+                continue;
+            }
+
+            let Ok(neighbor_transform) = neighbor_query.get(neighbor) else {
+                continue;
+            };
+
+            let offset = position - neighbor_transform.translation().truncate();
+            let distance = offset.length();
+
+            //  units stacked exactly on top of each other split up in any direction
+            let direction = offset
+                .try_normalize()
+                .unwrap_or_else(|| Vec2::from_angle(entity.index() as f32));
+
+            separation += direction * (1.0 - distance / steering.separation_radius).max(0.0);
+        }
+
+        //  sidestep the nearest obstacle ahead, unless it is where the unit is heading
+        let mut avoidance = Vec2::ZERO;
+
+        if let Some(direction) = desired.try_normalize() {
+            let shape = ColliderShape::Circle {
+                radius: collider.shape.bounding_radius(),
+            };
+            let hit = spatial_query
+                .cast_shape(
+                    &shape,
+                    position,
+                    direction,
+                    steering.look_ahead,
+                    groups.filters,
+                )
+                .into_iter()
+                .filter(|hit| hit.entity != entity)
+                .find_map(|hit| {
+                    let (obstacle_transform, obstacle_collider) =
+                        obstacle_query.get(hit.entity).ok()?;
+                    let obstacle_position = obstacle_transform.translation().truncate();
+
+                    let is_destination = steering.destination.is_some_and(|destination| {
+                        destination.distance(obstacle_position)
+                            <= obstacle_collider.shape.bounding_radius()
+                    });
+
+                    (!is_destination).then_some((obstacle_position, hit.distance))
+                });
+
+            if let Some((obstacle_position, distance)) = hit {
+                let away = position - obstacle_position;
+                let lateral = (away - direction * away.dot(direction))
+                    .try_normalize()
+                    .unwrap_or(direction.perp());
+
+                avoidance = lateral * (1.0 - distance / steering.look_ahead);
+            }
+        }
+
+        let steered = (desired
+            + (separation.clamp_length_max(1.0) * steering.weights.separation
+                + avoidance * steering.weights.avoidance)
+                * steering.speed)
+            .clamp_length_max(steering.speed);
+
+        //  turn towards the steered velocity on top of whatever else moves the unit
+        let change = steered - velocity.value.truncate();
+
+        acceleration.value = (change / time.delta_seconds())
+            .clamp_length_max(steering.max_force)
+            .extend(0.0);
+    }
+}
@@ -1,26 +1,38 @@
 use bevy::prelude::*;
 
 use crate::{
-    collisions::{Collider, CollisionGroups},
-    detection::{DetectionGroups, Target},
+    collisions::{Collider, CollisionGroups, Static},
+    damage::{Armor, InvulnerableOnHit, Shield},
+    despawn::Bounty,
+    detection::{DetectionGroups, Occluder, Target},
     footman::spawn_footman,
     group::Group,
-    health::Health,
+    health::{Health, HealthBar, Regeneration},
     schedule::InGameSet,
+    shape::ColliderShape,
 };
 
 const SPRITE_LAYER: f32 = -1.0;
-const COLLIDER_RADIUS: f32 = 60.0;
+const COLLIDER_HALF_EXTENTS: Vec2 = Vec2::new(60.0, 60.0);
 const HEALTH: f32 = 500.0;
+const ARMOR: f32 = 2.0;
+const BOUNTY: f32 = 100.0;
+const REGENERATION: f32 = 2.0;
+const SHIELD: f32 = 100.0;
+const SHIELD_RECHARGE_RATE: f32 = 20.0;
+const SHIELD_RECHARGE_DELAY: f32 = 4.0;
+const INVULNERABILITY_DURATION: f32 = 0.1;
+const HEALTH_BAR_WIDTH: f32 = 100.0;
+const HEALTH_BAR_OFFSET: f32 = 72.0;
 const SPAWN_RATE: f32 = 10.0;
-const SPAWN_OFFSET: Vec3 = Vec3::new(0.0, -46.0, 0.0);
+const SPAWN_OFFSET: Vec3 = Vec3::new(0.0, -80.0, 0.0);
 
 pub struct TowerPlugin;
 
 impl Plugin for TowerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, spawn_tower)
-            .add_systems(Update, spawn_enemy.in_set(InGameSet::EntityUpdates));
+            .add_systems(FixedUpdate, spawn_enemy.in_set(InGameSet::EntityUpdates));
     }
 }
 
@@ -45,11 +57,25 @@ fn spawn_tower(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             ..default()
         },
-        Collider::new(COLLIDER_RADIUS),
+        Collider::new(ColliderShape::Aabb {
+            half_extents: COLLIDER_HALF_EXTENTS,
+        }),
         CollisionGroups::new(Group::ENEMY | Group::STRUCTURE, Group::NONE),
+        Static,
         DetectionGroups::new(Group::ENEMY | Group::STRUCTURE, Group::NONE),
         Target,
-        Health::new(HEALTH),
+        Occluder,
+        (
+            Health::new(HEALTH),
+            Regeneration::new(REGENERATION),
+            HealthBar::new(HEALTH_BAR_WIDTH, HEALTH_BAR_OFFSET),
+        ),
+        (
+            Armor::new(ARMOR),
+            Shield::new(SHIELD, SHIELD_RECHARGE_RATE, SHIELD_RECHARGE_DELAY),
+            InvulnerableOnHit::new(INVULNERABILITY_DURATION),
+        ),
+        Bounty::new(BOUNTY),
         Tower {
             spawn_rate: Timer::from_seconds(SPAWN_RATE, TimerMode::Repeating),
         },
@@ -8,7 +8,13 @@ use bevy::{
     },
 };
 
-use crate::Mana;
+use crate::{
+    detection::{Cloak, DetectionGroups},
+    group::Group,
+    Mana,
+};
+
+const CLOAKED_ALPHA: f32 = 0.4;
 
 pub struct GameUI;
 
@@ -18,7 +24,7 @@ pub struct ManaText;
 impl Plugin for GameUI {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, spawn_game_ui)
-            .add_systems(Update, update_mana_ui);
+            .add_systems(Update, (update_mana_ui, update_cloak_transparency));
     }
 }
 
@@ -60,3 +66,20 @@ fn update_mana_ui(mut texts: Query<&mut Text, With<ManaText>>, mana: Res<Mana>)
         text.sections[0].value = format!("Mana: ${:?}", mana.0);
     }
 }
+
+/// Cloaked allies fade out, so the player can tell which of them are hidden.
+fn update_cloak_transparency(mut sprites: Query<(&mut Sprite, &Cloak, &DetectionGroups)>) {
+    for (mut sprite, cloak, groups) in &mut sprites {
+        if (groups.memberships & Group::ALLY) == Group::NONE {
+            continue;
+        }
+
+        let alpha = if cloak.is_revealed() {
+            1.0
+        } else {
+            1.0 - cloak.value * (1.0 - CLOAKED_ALPHA)
+        };
+
+        sprite.color.set_a(alpha);
+    }
+}